@@ -1,70 +1,92 @@
-use std::io::Read;
-use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::visualizer::{VisualizerSettings, BAR_MAX};
+
+/// Delay before restarting a crashed CAVA; it doubles with each crash up to
+/// the maximum, and resets once CAVA has stayed up for `STABLE_RUN`.
+const MIN_RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+const STABLE_RUN: Duration = Duration::from_secs(30);
 
 /// Manages a CAVA audio visualizer subprocess that outputs raw bar data.
 /// Reads the user's config from ~/.config/cava/config and overrides
 /// the output section to use raw binary mode for internal rendering.
+/// CAVA is restarted if it exits, and can be stopped (SIGSTOP) while
+/// there's nothing to show.
 pub struct CavaVisualizer {
-    process: Option<Child>,
-    bars: Arc<Mutex<Vec<u8>>>,
+    process: Arc<Mutex<Option<Child>>>,
+    bars: Arc<Mutex<Vec<u16>>>,
     num_bars: usize,
-    temp_config_path: String,
+    temp_config_path: PathBuf,
+    paused: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
 }
 
 impl CavaVisualizer {
-    /// Spawn a new CAVA process with `num_bars` bars.
-    /// Returns None if cava is not installed or fails to start.
-    pub fn new(num_bars: usize) -> Option<Self> {
-        let temp_config_path = Self::create_temp_config(num_bars)?;
-
-        let mut child = Command::new("cava")
-            .arg("-p")
-            .arg(&temp_config_path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .stdin(Stdio::null())
-            .spawn()
-            .ok()?;
-
-        let bars = Arc::new(Mutex::new(vec![0u8; num_bars]));
-        let bars_clone = bars.clone();
-        let bar_count = num_bars;
-
-        if let Some(stdout) = child.stdout.take() {
-            thread::spawn(move || {
-                let mut reader = stdout;
-                let mut buf = vec![0u8; bar_count];
-                loop {
-                    match reader.read_exact(&mut buf) {
-                        Ok(()) => {
-                            if let Ok(mut bars) = bars_clone.lock() {
-                                bars.copy_from_slice(&buf);
-                            }
-                        }
-                        Err(_) => break,
-                    }
-                }
-            });
-        }
+    /// Spawn a new CAVA process with the bar count, channels and precision
+    /// from `settings`. Returns None if cava is not installed or fails to start.
+    pub fn new(settings: VisualizerSettings) -> Option<Self> {
+        let num_bars = settings.bars;
+        let temp_config_path = Self::create_temp_config(&settings)?;
+        let mut child = match spawn(&temp_config_path) {
+            Some(child) => child,
+            None => {
+                let _ = std::fs::remove_file(&temp_config_path);
+                return None;
+            }
+        };
+        let stdout = child.stdout.take()?;
 
-        Some(Self {
-            process: Some(child),
-            bars,
+        let visualizer = Self {
+            process: Arc::new(Mutex::new(Some(child))),
+            bars: Arc::new(Mutex::new(vec![0; num_bars])),
             num_bars,
             temp_config_path,
-        })
+            paused: Arc::new(AtomicBool::new(false)),
+            stop: Arc::new(AtomicBool::new(false)),
+        };
+        let supervisor = Supervisor {
+            process: visualizer.process.clone(),
+            bars: visualizer.bars.clone(),
+            config_path: visualizer.temp_config_path.clone(),
+            high_precision: settings.high_precision,
+            paused: visualizer.paused.clone(),
+            stop: visualizer.stop.clone(),
+        };
+        thread::spawn(move || supervisor.run(stdout));
+
+        Some(visualizer)
     }
 
-    /// Get the current bar values (0–255 each).
-    pub fn get_bars(&self) -> Vec<u8> {
+    /// Stop CAVA while nothing would be shown (and the bars drop to zero),
+    /// or let it continue.
+    pub fn set_paused(&self, paused: bool) {
+        if self.paused.swap(paused, Ordering::SeqCst) == paused {
+            return;
+        }
+        if let Some(ref child) = *self.process.lock().unwrap_or_else(|e| e.into_inner()) {
+            signal(child, if paused { libc::SIGSTOP } else { libc::SIGCONT });
+        }
+        if paused {
+            self.bars.lock().unwrap_or_else(|e| e.into_inner()).fill(0);
+        }
+    }
+
+    /// Get the current bar values (0–`BAR_MAX` each).
+    pub fn get_bars(&self) -> Vec<u16> {
         self.bars.lock().unwrap_or_else(|e| e.into_inner()).clone()
     }
 
     /// Get a clone of the Arc holding bar data, for sharing with draw callbacks.
-    pub fn get_bars_arc(&self) -> Arc<Mutex<Vec<u8>>> {
+    pub fn get_bars_arc(&self) -> Arc<Mutex<Vec<u16>>> {
         Arc::clone(&self.bars)
     }
 
@@ -74,7 +96,8 @@ impl CavaVisualizer {
     }
 
     /// Build a temporary config that imports the user's settings but forces raw output.
-    fn create_temp_config(num_bars: usize) -> Option<String> {
+    /// It goes in `$XDG_RUNTIME_DIR` (the temp dir if unset), readable only by us.
+    fn create_temp_config(settings: &VisualizerSettings) -> Option<PathBuf> {
         let home = std::env::var("HOME").ok()?;
         let user_config_path = PathBuf::from(&home).join(".config/cava/config");
 
@@ -84,7 +107,9 @@ impl CavaVisualizer {
             String::new()
         };
 
-        let temp_path = format!("/tmp/bard_cava_{}.conf", std::process::id());
+        let temp_path = dirs::runtime_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(format!("bard-cava-{}.conf", std::process::id()));
 
         // Copy user config but strip [output] and [general] bars setting
         // so we can override them
@@ -127,22 +152,158 @@ impl CavaVisualizer {
             method = raw\n\
             raw_target = /dev/stdout\n\
             data_format = binary\n\
-            bit_format = 8bit\n\
-            channels = mono\n",
-            num_bars
+            bit_format = {}\n\
+            channels = {}\n",
+            settings.bars,
+            if settings.high_precision { "16bit" } else { "8bit" },
+            if settings.stereo { "stereo" } else { "mono" },
         ));
 
-        std::fs::write(&temp_path, &config).ok()?;
+        // A fresh file, so nothing planted at the path (like a symlink) is followed
+        let _ = std::fs::remove_file(&temp_path);
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&temp_path)
+            .and_then(|mut file| file.write_all(config.as_bytes()))
+            .map_err(|e| log::warn!("Can't write CAVA config {}: {}", temp_path.display(), e))
+            .ok()?;
         Some(temp_path)
     }
 }
 
 impl Drop for CavaVisualizer {
     fn drop(&mut self) {
-        if let Some(mut process) = self.process.take() {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(mut process) = self.process.lock().unwrap_or_else(|e| e.into_inner()).take() {
             let _ = process.kill();
             let _ = process.wait();
         }
         let _ = std::fs::remove_file(&self.temp_config_path);
     }
 }
+
+fn spawn(config_path: &Path) -> Option<Child> {
+    Command::new("cava")
+        .arg("-p")
+        .arg(config_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .stdin(Stdio::null())
+        .spawn()
+        .ok()
+}
+
+fn signal(child: &Child, signal: libc::c_int) {
+    // SAFETY: kill(2) only sends a signal; the pid is our own unreaped child
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, signal);
+    }
+}
+
+/// The thread that reads CAVA's output and restarts it when it exits.
+struct Supervisor {
+    process: Arc<Mutex<Option<Child>>>,
+    bars: Arc<Mutex<Vec<u16>>>,
+    config_path: PathBuf,
+    /// Whether CAVA writes 16-bit rather than 8-bit heights
+    high_precision: bool,
+    paused: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Supervisor {
+    fn run(self, stdout: ChildStdout) {
+        let mut stdout = Some(stdout);
+        let mut delay = MIN_RESTART_DELAY;
+        while !self.stop.load(Ordering::SeqCst) {
+            if let Some(out) = stdout.take() {
+                let started = Instant::now();
+                self.read_bars(out);
+                if self.stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                let status = self
+                    .process
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .take()
+                    .and_then(|mut child| {
+                        let _ = child.kill();
+                        child.wait().ok()
+                    });
+                self.bars.lock().unwrap_or_else(|e| e.into_inner()).fill(0);
+                if started.elapsed() >= STABLE_RUN {
+                    delay = MIN_RESTART_DELAY;
+                }
+                match status {
+                    Some(status) => log::warn!("CAVA exited ({}); restarting in {:?}", status, delay),
+                    None => log::warn!("CAVA output ended; restarting in {:?}", delay),
+                }
+            }
+
+            if !self.sleep(delay) {
+                break;
+            }
+            delay = (delay * 2).min(MAX_RESTART_DELAY);
+            stdout = self.restart();
+        }
+    }
+
+    /// Copy frames of bar heights until CAVA's output ends.
+    fn read_bars(&self, mut stdout: ChildStdout) {
+        let bytes_per_bar = if self.high_precision { 2 } else { 1 };
+        let mut buf = vec![0u8; self.bars.lock().unwrap_or_else(|e| e.into_inner()).len() * bytes_per_bar];
+        while stdout.read_exact(&mut buf).is_ok() {
+            // Frames still in the pipe when CAVA was stopped would undo set_paused
+            if self.paused.load(Ordering::SeqCst) {
+                continue;
+            }
+            if let Ok(mut bars) = self.bars.lock() {
+                if self.high_precision {
+                    for (bar, b) in bars.iter_mut().zip(buf.chunks_exact(2)) {
+                        *bar = u16::from_ne_bytes([b[0], b[1]]);
+                    }
+                } else {
+                    // Scale 0–255 up to the full range
+                    for (bar, &b) in bars.iter_mut().zip(&buf) {
+                        *bar = b as u16 * (BAR_MAX / 255);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Start a new CAVA (stopped right away if paused); its output, or None
+    /// if it didn't start.
+    fn restart(&self) -> Option<ChildStdout> {
+        let mut process = self.process.lock().unwrap_or_else(|e| e.into_inner());
+        if self.stop.load(Ordering::SeqCst) {
+            return None;
+        }
+        let Some(mut child) = spawn(&self.config_path) else {
+            log::warn!("Can't restart CAVA");
+            return None;
+        };
+        if self.paused.load(Ordering::SeqCst) {
+            signal(&child, libc::SIGSTOP);
+        }
+        let stdout = child.stdout.take();
+        *process = Some(child);
+        stdout
+    }
+
+    /// Sleep for `duration`, waking early if the visualizer is dropped;
+    /// false if it was.
+    fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        while Instant::now() < deadline {
+            if self.stop.load(Ordering::SeqCst) {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(100).min(deadline.saturating_duration_since(Instant::now())));
+        }
+        !self.stop.load(Ordering::SeqCst)
+    }
+}
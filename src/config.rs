@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// User settings from `~/.config/bard/config`.
+///
+/// Same INI-style layout as CAVA's config: `[section]` headers, `key = value`
+/// pairs, and `#` or `;` comments. Missing files and keys fall back to defaults.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    values: HashMap<(String, String), String>,
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("bard")
+            .join("config")
+    }
+
+    /// Load the user's config, or an empty one if it doesn't exist.
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut values = HashMap::new();
+        let mut section = String::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_lowercase();
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim().trim_matches('"').to_string();
+                values.insert((section.clone(), key.trim().to_lowercase()), value);
+            }
+        }
+
+        Self { values }
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.values
+            .get(&(section.to_string(), key.to_string()))
+            .map(String::as_str)
+    }
+
+    /// Boolean setting; accepts `true/false`, `yes/no`, `on/off` and `1/0`.
+    pub fn get_bool(&self, section: &str, key: &str, default: bool) -> bool {
+        match self.get(section, key).map(str::to_lowercase).as_deref() {
+            Some("true" | "yes" | "on" | "1") => true,
+            Some("false" | "no" | "off" | "0") => false,
+            _ => default,
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Sample rate requested from ffmpeg in the fallback path
+/// (low rate = fast extraction, still enough resolution for a waveform).
+const FFMPEG_SAMPLE_RATE: u32 = 8000;
+
+/// Receives decoded audio as stereo frames, samples in -1.0..=1.0.
+/// Mono sources are duplicated to both channels; extra channels are dropped.
+pub trait PcmSink {
+    /// Called before the first frame, and again if decoding restarts with
+    /// another backend — implementations should reset any partial state.
+    /// `total_frames` is the track length when the container declares it;
+    /// `channels` is the source's channel count before the stereo mapping.
+    fn begin(&mut self, sample_rate: u32, total_frames: Option<u64>, channels: usize);
+    fn frame(&mut self, left: f32, right: f32);
+
+    /// Polled between packets; returning true abandons the decode.
+    fn cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Feeds both sinks, so several analyses can share one decode.
+impl<A: PcmSink, B: PcmSink> PcmSink for (A, B) {
+    fn begin(&mut self, sample_rate: u32, total_frames: Option<u64>, channels: usize) {
+        self.0.begin(sample_rate, total_frames, channels);
+        self.1.begin(sample_rate, total_frames, channels);
+    }
+
+    fn frame(&mut self, left: f32, right: f32) {
+        self.0.frame(left, right);
+        self.1.frame(left, right);
+    }
+
+    fn cancelled(&self) -> bool {
+        self.0.cancelled() || self.1.cancelled()
+    }
+}
+
+/// Decode `path` into `sink`, streaming rather than buffering the whole track.
+///
+/// Tries the in-process decoder first (MP3, FLAC, Vorbis, AAC/ALAC in MP4,
+/// WAV) and falls back to ffmpeg for anything it can't open, such as Opus.
+/// Returns false if no backend produced any audio or the sink cancelled.
+/// This is CPU-intensive and should be called from a background thread.
+pub fn decode_stereo(path: &Path, sink: &mut dyn PcmSink) -> bool {
+    match decode_native(path, sink) {
+        _ if sink.cancelled() => false,
+        Some(frames) if frames > 0 => true,
+        _ => {
+            log::debug!("Native decode failed for {}, trying ffmpeg", path.display());
+            decode_ffmpeg(path, sink).is_some_and(|frames| frames > 0) && !sink.cancelled()
+        }
+    }
+}
+
+/// Decode with symphonia. None if the container or codec isn't supported;
+/// otherwise the number of frames delivered (corrupt packets are skipped).
+fn decode_native(path: &Path, sink: &mut dyn PcmSink) -> Option<u64> {
+    let file = File::open(path).ok()?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+        .ok()?;
+    let mut format = probed.format;
+
+    let track = format.tracks().iter().find(|t| t.codec_params.codec != CODEC_TYPE_NULL)?;
+    let track_id = track.id;
+    let sample_rate = track.codec_params.sample_rate?;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .ok()?;
+
+    let source_channels = track.codec_params.channels.map_or(2, |c| c.count());
+    sink.begin(sample_rate, track.codec_params.n_frames, source_channels);
+    let mut buf: Option<SampleBuffer<f32>> = None;
+    let mut frames: u64 = 0;
+
+    // Stops at end of stream (or a chained stream we don't follow)
+    while let Ok(packet) = format.next_packet() {
+        if sink.cancelled() {
+            break;
+        }
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(Error::DecodeError(_)) => continue,
+            Err(_) => break,
+        };
+
+        let spec = *decoded.spec();
+        let channels = spec.channels.count().max(1);
+        let needed = decoded.capacity() * channels;
+        if buf.as_ref().is_none_or(|b| b.capacity() < needed) {
+            buf = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+        }
+        let Some(buf) = buf.as_mut() else { break };
+        buf.copy_interleaved_ref(decoded);
+
+        for frame in buf.samples().chunks_exact(channels) {
+            let left = frame[0];
+            let right = if channels > 1 { frame[1] } else { left };
+            sink.frame(left, right);
+        }
+        frames += (buf.samples().len() / channels) as u64;
+    }
+
+    Some(frames)
+}
+
+/// Decode by piping ffmpeg's raw s16le stereo output, a chunk at a time.
+/// None if ffmpeg isn't installed or can't be started.
+fn decode_ffmpeg(path: &Path, sink: &mut dyn PcmSink) -> Option<u64> {
+    let mut child = Command::new("ffmpeg")
+        .arg("-i").arg(path)
+        .args([
+            "-ac", "2",          // stereo
+            "-ar", &FFMPEG_SAMPLE_RATE.to_string(),
+            "-f", "s16le",       // raw signed 16-bit little-endian
+            "-acodec", "pcm_s16le",
+            "-v", "quiet",
+            "-",                 // output to stdout
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let mut stdout = BufReader::new(child.stdout.take()?);
+    sink.begin(FFMPEG_SAMPLE_RATE, None, 2);
+
+    // Each frame = 4 bytes (2 bytes left + 2 bytes right); keep a partial
+    // frame's bytes around when a read ends mid-frame
+    let mut chunk = vec![0u8; 64 * 1024];
+    let mut filled = 0;
+    let mut frames: u64 = 0;
+    loop {
+        if sink.cancelled() {
+            let _ = child.kill();
+            break;
+        }
+        let n = match stdout.read(&mut chunk[filled..]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        filled += n;
+        let whole = filled - filled % 4;
+        for f in chunk[..whole].chunks_exact(4) {
+            let left = i16::from_le_bytes([f[0], f[1]]) as f32 / 32768.0;
+            let right = i16::from_le_bytes([f[2], f[3]]) as f32 / 32768.0;
+            sink.frame(left, right);
+        }
+        frames += (whole / 4) as u64;
+        chunk.copy_within(whole..filled, 0);
+        filled -= whole;
+    }
+
+    let _ = child.wait();
+    Some(frames)
+}
@@ -0,0 +1,367 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{bail, Result};
+
+use crate::decode::{self, PcmSink};
+
+/// Loudness is measured in 100 ms sub-blocks; gating blocks (400 ms) and
+/// short-term windows (3 s) are built from consecutive sub-blocks.
+const SUB_BLOCK_SECS: f64 = 0.1;
+const GATING_SUB_BLOCKS: usize = 4;
+const SHORT_TERM_SUB_BLOCKS: usize = 30;
+
+/// Absolute gate for integrated loudness and loudness range (EBU R128).
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// ReplayGain 2.0 reference level.
+pub const REPLAYGAIN_REFERENCE_LUFS: f64 = -18.0;
+
+/// Below this rate (the ffmpeg fallback decodes at 8 kHz) the K-weighting
+/// and true-peak estimate are too far off to write tags from.
+const MIN_SAMPLE_RATE: u32 = 32000;
+
+/// Taps per phase of the 4× true-peak interpolator.
+const TRUE_PEAK_TAPS: usize = 12;
+const OVERSAMPLE: usize = 4;
+
+/// Loudness measurement of one track, kept in a form that album values can
+/// be derived from: gating has to run over all of an album's blocks at once.
+#[derive(Clone, Debug, Default)]
+pub struct TrackLoudness {
+    /// K-weighted mean square (summed over channels) of each 100 ms sub-block
+    pub sub_blocks: Vec<f64>,
+    /// Highest 4×-oversampled sample, linear
+    pub true_peak: f64,
+}
+
+/// Integrated loudness, loudness range and true peak of a track or album.
+#[derive(Clone, Copy, Debug)]
+pub struct LoudnessSummary {
+    pub integrated: f64,
+    pub range: f64,
+    pub true_peak: f64,
+}
+
+impl LoudnessSummary {
+    pub fn true_peak_db(&self) -> f64 {
+        20.0 * self.true_peak.max(1e-9).log10()
+    }
+
+    /// ReplayGain 2.0 gain in dB: what brings the track to -18 LUFS.
+    pub fn replaygain(&self) -> f64 {
+        REPLAYGAIN_REFERENCE_LUFS - self.integrated
+    }
+}
+
+impl TrackLoudness {
+    /// None for silent tracks, where integrated loudness is undefined.
+    pub fn summary(&self) -> Option<LoudnessSummary> {
+        summarize(std::slice::from_ref(self))
+    }
+}
+
+/// Album loudness over all tracks, as if they were played back to back.
+pub fn album_summary(tracks: &[TrackLoudness]) -> Option<LoudnessSummary> {
+    summarize(tracks)
+}
+
+fn summarize(tracks: &[TrackLoudness]) -> Option<LoudnessSummary> {
+    // Blocks never straddle two tracks
+    let windows = |len: usize| -> Vec<f64> {
+        tracks
+            .iter()
+            .flat_map(|t| {
+                t.sub_blocks
+                    .windows(len)
+                    .map(move |w| w.iter().sum::<f64>() / len as f64)
+            })
+            .collect()
+    };
+
+    let integrated = gated_loudness(&windows(GATING_SUB_BLOCKS), -10.0)?;
+    let range = loudness_range(&windows(SHORT_TERM_SUB_BLOCKS)).unwrap_or(0.0);
+    let true_peak = tracks.iter().map(|t| t.true_peak).fold(0.0, f64::max);
+    Some(LoudnessSummary { integrated, range, true_peak })
+}
+
+fn energy_to_lufs(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+fn lufs_to_energy(lufs: f64) -> f64 {
+    10f64.powf((lufs + 0.691) / 10.0)
+}
+
+/// Mean energy of the blocks above `gate`, if any are.
+fn mean_above(blocks: &[f64], gate: f64) -> Option<f64> {
+    let (sum, n) = blocks
+        .iter()
+        .filter(|&&e| e > gate)
+        .fold((0.0, 0usize), |(sum, n), &e| (sum + e, n + 1));
+    (n > 0).then(|| sum / n as f64)
+}
+
+/// The relative gate: `offset` LU below the mean of the blocks that pass
+/// the absolute gate. None if nothing does.
+fn relative_gate(blocks: &[f64], offset: f64) -> Option<f64> {
+    let absolute = lufs_to_energy(ABSOLUTE_GATE_LUFS);
+    let mean = mean_above(blocks, absolute)?;
+    Some(lufs_to_energy(energy_to_lufs(mean) + offset).max(absolute))
+}
+
+/// BS.1770 two-stage gating: drop blocks under -70 LUFS, then those more
+/// than `offset` LU below the mean of the rest.
+fn gated_loudness(blocks: &[f64], offset: f64) -> Option<f64> {
+    mean_above(blocks, relative_gate(blocks, offset)?).map(energy_to_lufs)
+}
+
+/// EBU Tech 3342: spread between the 10th and 95th percentile of the
+/// gated short-term loudness values.
+fn loudness_range(short_term: &[f64]) -> Option<f64> {
+    let gate = relative_gate(short_term, -20.0)?;
+    let mut values: Vec<f64> = short_term
+        .iter()
+        .filter(|&&e| e > gate)
+        .map(|&e| energy_to_lufs(e))
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let percentile = |p: f64| values[((values.len() - 1) as f64 * p).round() as usize];
+    Some(percentile(0.95) - percentile(0.10))
+}
+
+/// One biquad section in direct form I.
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x0: f64) -> f64 {
+        let y0 = self.b[0] * x0 + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x0, self.x[0]];
+        self.y = [y0, self.y[0]];
+        y0
+    }
+}
+
+/// The BS.1770 K-weighting filter (high shelf + high pass), designed for
+/// any sample rate rather than only the 48 kHz coefficients in the spec.
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let fs = sample_rate as f64;
+
+    let (f0, gain, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (std::f64::consts::PI * f0 / fs).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        ..Default::default()
+    };
+
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (std::f64::consts::PI * f0 / fs).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        ..Default::default()
+    };
+
+    [shelf, high_pass]
+}
+
+/// Polyphase coefficients of a Hann-windowed sinc for 4× interpolation.
+fn true_peak_phases() -> [[f64; TRUE_PEAK_TAPS]; OVERSAMPLE] {
+    let len = TRUE_PEAK_TAPS * OVERSAMPLE;
+    let mut phases = [[0.0; TRUE_PEAK_TAPS]; OVERSAMPLE];
+    for n in 0..len {
+        let t = (n as f64 - (len - 1) as f64 / 2.0) / OVERSAMPLE as f64;
+        let sinc = if t == 0.0 {
+            1.0
+        } else {
+            (std::f64::consts::PI * t).sin() / (std::f64::consts::PI * t)
+        };
+        let window = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * n as f64 / (len - 1) as f64).cos();
+        phases[n % OVERSAMPLE][n / OVERSAMPLE] = sinc * window;
+    }
+    phases
+}
+
+#[derive(Default)]
+struct Channel {
+    filter: [Biquad; 2],
+    sum_sq: f64,
+    history: [f64; TRUE_PEAK_TAPS],
+}
+
+/// Streaming EBU R128 meter; feed it through `decode::decode_stereo`.
+#[derive(Default)]
+pub struct LoudnessMeter {
+    channels: [Channel; 2],
+    /// Mono sources arrive duplicated on both channels but count once
+    mono: bool,
+    frames_per_sub_block: usize,
+    frames: usize,
+    phases: [[f64; TRUE_PEAK_TAPS]; OVERSAMPLE],
+    result: TrackLoudness,
+    usable: bool,
+}
+
+impl LoudnessMeter {
+    /// The measurement, or None if decoding was too coarse to trust.
+    pub fn finish(self) -> Option<TrackLoudness> {
+        (self.usable && !self.result.sub_blocks.is_empty()).then_some(self.result)
+    }
+}
+
+impl PcmSink for LoudnessMeter {
+    fn begin(&mut self, sample_rate: u32, _total_frames: Option<u64>, channels: usize) {
+        *self = Self {
+            channels: [0, 1].map(|_| Channel { filter: k_weighting(sample_rate), ..Default::default() }),
+            mono: channels == 1,
+            frames_per_sub_block: ((sample_rate as f64 * SUB_BLOCK_SECS) as usize).max(1),
+            phases: true_peak_phases(),
+            usable: sample_rate >= MIN_SAMPLE_RATE,
+            ..Default::default()
+        };
+    }
+
+    fn frame(&mut self, left: f32, right: f32) {
+        if !self.usable {
+            return;
+        }
+        let active = if self.mono { 1 } else { 2 };
+        for (ch, sample) in self.channels.iter_mut().zip([left, right]).take(active) {
+            let x = sample as f64;
+            let weighted = ch.filter.iter_mut().fold(x, |acc, f| f.process(acc));
+            ch.sum_sq += weighted * weighted;
+
+            ch.history.rotate_right(1);
+            ch.history[0] = x;
+            for phase in &self.phases {
+                let y: f64 = phase.iter().zip(&ch.history).map(|(c, h)| c * h).sum();
+                self.result.true_peak = self.result.true_peak.max(y.abs());
+            }
+            self.result.true_peak = self.result.true_peak.max(x.abs());
+        }
+
+        self.frames += 1;
+        if self.frames == self.frames_per_sub_block {
+            let n = self.frames as f64;
+            let energy = self.channels.iter_mut().map(|ch| std::mem::take(&mut ch.sum_sq) / n).sum();
+            self.result.sub_blocks.push(energy);
+            self.frames = 0;
+        }
+    }
+}
+
+/// Measure a file on its own (when no waveform decode is running for it).
+/// None if it can't be decoded natively, is silent, or `cancel` was set.
+/// This is CPU-intensive and should be called from a background thread.
+pub fn analyze_file(path: &Path, cancel: &AtomicBool) -> Option<TrackLoudness> {
+    struct Cancellable<'a> {
+        meter: LoudnessMeter,
+        cancel: &'a AtomicBool,
+    }
+    impl PcmSink for Cancellable<'_> {
+        fn begin(&mut self, sample_rate: u32, total_frames: Option<u64>, channels: usize) {
+            self.meter.begin(sample_rate, total_frames, channels);
+        }
+        fn frame(&mut self, left: f32, right: f32) {
+            self.meter.frame(left, right);
+        }
+        fn cancelled(&self) -> bool {
+            self.cancel.load(Ordering::Relaxed)
+        }
+    }
+
+    let mut sink = Cancellable { meter: LoudnessMeter::default(), cancel };
+    if !decode::decode_stereo(path, &mut sink) {
+        return None;
+    }
+    sink.meter.finish()
+}
+
+/// Tag values in the usual ReplayGain notation.
+fn format_gain(db: f64) -> String {
+    format!("{:+.2} dB", db)
+}
+
+fn format_peak(peak: f64) -> String {
+    format!("{:.6}", peak)
+}
+
+/// Whether Bard can write tags to this file: MP3 (ID3) and FLAC (Vorbis comments).
+pub fn can_write_tags(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| matches!(ext.as_str(), "mp3" | "flac"))
+}
+
+/// Read a file's ID3 tag for rewriting. A file without one gets a fresh tag;
+/// any other read error is returned so existing frames are never dropped.
+pub fn read_id3(path: &Path) -> Result<id3::Tag> {
+    match id3::Tag::read_from_path(path) {
+        Ok(tag) => Ok(tag),
+        Err(id3::Error { kind: id3::ErrorKind::NoTag, .. }) => Ok(id3::Tag::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write a tag from [`read_id3`] back in the version it was read with, so
+/// ID3v2.3 files stay readable by players without v2.4 support. Fresh tags
+/// are v2.4, and the rare v2.2 tag is upgraded to v2.3.
+pub fn write_id3(tag: &id3::Tag, path: &Path) -> Result<()> {
+    let version = match tag.version() {
+        id3::Version::Id3v22 => id3::Version::Id3v23,
+        version => version,
+    };
+    tag.write_to_path(path, version)?;
+    Ok(())
+}
+
+/// Write REPLAYGAIN_TRACK_* (and REPLAYGAIN_ALBUM_* when given) tags, as ID3
+/// TXXX frames for MP3 and Vorbis comments for FLAC, replacing older values.
+pub fn write_replaygain(path: &Path, track: &LoudnessSummary, album: Option<&LoudnessSummary>) -> Result<()> {
+    let mut tags = vec![
+        ("REPLAYGAIN_TRACK_GAIN", format_gain(track.replaygain())),
+        ("REPLAYGAIN_TRACK_PEAK", format_peak(track.true_peak)),
+    ];
+    if let Some(album) = album {
+        tags.push(("REPLAYGAIN_ALBUM_GAIN", format_gain(album.replaygain())));
+        tags.push(("REPLAYGAIN_ALBUM_PEAK", format_peak(album.true_peak)));
+    }
+
+    let ext = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+    match ext.as_deref() {
+        Some("mp3") => {
+            use id3::TagLike;
+            let mut tag = read_id3(path)?;
+            for (key, value) in tags {
+                tag.remove_extended_text(Some(key), None);
+                tag.add_frame(id3::frame::ExtendedText { description: key.to_string(), value });
+            }
+            write_id3(&tag, path)?;
+        }
+        Some("flac") => {
+            let mut tag = metaflac::Tag::read_from_path(path)?;
+            for (key, value) in tags {
+                tag.set_vorbis(key, vec![value]);
+            }
+            tag.save()?;
+        }
+        _ => bail!("writing tags to {} files isn't supported", ext.as_deref().unwrap_or("these")),
+    }
+    Ok(())
+}
@@ -1,45 +1,122 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::lyrics_formats;
+
+/// Step for the live lyrics offset, in milliseconds.
+pub const OFFSET_STEP_MS: i64 = 100;
 
 #[derive(Debug, Clone)]
 pub struct LyricLine {
     pub timestamp: f64,
     pub text: String,
+    /// Secondary track (translation or romanization) shown under the line
+    pub translation: Option<String>,
+    /// Per-word (or per-syllable) timing for karaoke highlighting, if the
+    /// source format has it; joining the word texts gives the line
+    pub words: Vec<LyricWord>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LyricWord {
+    pub start: f64,
+    pub text: String,
 }
 
 pub struct LRCParser {
     pub lines: Vec<LyricLine>,
+    /// ID tags such as `[ar:Artist]` or `[by:Someone]`, kept in file order
+    pub tags: Vec<(String, String)>,
+    /// Timing offset in milliseconds (`[offset:]` tag); positive shows lyrics earlier
+    pub offset_ms: i64,
 }
 
 impl LRCParser {
+    /// Wrap lines produced by another format parser (SRT, VTT, TTML).
+    pub fn from_lines(lines: Vec<LyricLine>) -> Self {
+        Self { lines, tags: Vec::new(), offset_ms: 0 }
+    }
+
     pub fn from_file<P: AsRef<Path>>(path: P) -> Option<Self> {
-        let file = File::open(path).ok()?;
-        let reader = BufReader::new(file);
-        
+        Self::parse(&std::fs::read_to_string(path).ok()?)
+    }
+
+    /// Parse LRC text: timed `[mm:ss.xx]` lines plus ID and `[offset:]` tags.
+    pub fn parse(text: &str) -> Option<Self> {
         let mut lines = Vec::new();
+        let mut tags = Vec::new();
+        let mut offset_ms = 0;
         let time_regex = regex::Regex::new(r"\[(\d+):(\d+\.\d+)\](.*)").ok()?;
+        let tag_regex = regex::Regex::new(r"^\[([A-Za-z]+):(.*)\]$").ok()?;
 
-        for line in reader.lines() {
-            let line = line.ok()?;
-            
-            if let Some(captures) = time_regex.captures(&line) {
+        for line in text.lines() {
+            if let Some(captures) = time_regex.captures(line) {
                 let minutes: u32 = captures.get(1)?.as_str().parse().ok()?;
                 let seconds: f64 = captures.get(2)?.as_str().parse().ok()?;
                 let text = captures.get(3)?.as_str().trim().to_string();
-                
+
                 let timestamp = minutes as f64 * 60.0 + seconds;
-                
-                lines.push(LyricLine { timestamp, text });
+
+                lines.push(LyricLine { timestamp, text, translation: None, words: Vec::new() });
+            } else if let Some(captures) = tag_regex.captures(line.trim()) {
+                let key = captures.get(1)?.as_str().to_string();
+                let value = captures.get(2)?.as_str().trim().to_string();
+                if key.eq_ignore_ascii_case("offset") {
+                    offset_ms = value.trim_start_matches('+').parse().unwrap_or(0);
+                } else {
+                    tags.push((key, value));
+                }
             }
         }
 
         lines.sort_by(|a, b| a.timestamp.partial_cmp(&b.timestamp).unwrap());
 
-        Some(Self { lines })
+        // Duplicate-timestamp convention: a second line stamped at the same
+        // time as the previous one is its translation
+        let mut merged: Vec<LyricLine> = Vec::with_capacity(lines.len());
+        for line in lines {
+            if let Some(prev) = merged.last_mut() {
+                let same_time = (line.timestamp - prev.timestamp).abs() < 0.001;
+                if same_time && prev.translation.is_none() && !line.text.is_empty() {
+                    prev.translation = Some(line.text);
+                    continue;
+                }
+            }
+            merged.push(line);
+        }
+
+        Some(Self { lines: merged, tags, offset_ms })
+    }
+
+    /// The offset in seconds, as added to the playback time when matching lines.
+    pub fn offset_secs(&self) -> f64 {
+        self.offset_ms as f64 / 1000.0
+    }
+
+    /// Fill in translations from a secondary track, matching each line to the
+    /// closest secondary line within half a second.
+    pub fn attach_translation(&mut self, secondary: &LRCParser) {
+        for line in self.lines.iter_mut().filter(|l| l.translation.is_none()) {
+            let closest = secondary.lines.iter()
+                .filter(|s| !s.text.is_empty())
+                .min_by(|a, b| {
+                    let da = (a.timestamp - line.timestamp).abs();
+                    let db = (b.timestamp - line.timestamp).abs();
+                    da.partial_cmp(&db).unwrap()
+                });
+            if let Some(sec) = closest {
+                if (sec.timestamp - line.timestamp).abs() <= 0.5 {
+                    line.translation = Some(sec.text.clone());
+                }
+            }
+        }
+    }
+
+    pub fn has_translation(&self) -> bool {
+        self.lines.iter().any(|l| l.translation.is_some())
     }
 
     pub fn get_current_line(&self, current_time: f64) -> Option<(usize, &str)> {
+        let current_time = current_time + self.offset_secs();
         for (i, line) in self.lines.iter().enumerate() {
             if i + 1 < self.lines.len() {
                 let next_timestamp = self.lines[i + 1].timestamp;
@@ -52,4 +129,222 @@ impl LRCParser {
         }
         None
     }
+
+    /// Serialize back to LRC text: ID tags first, then one `[mm:ss.xx]` line per lyric.
+    pub fn to_lrc_string(&self) -> String {
+        let mut out = String::new();
+        for (key, value) in &self.tags {
+            out.push_str(&format!("[{}:{}]\n", key, value));
+        }
+        if self.offset_ms != 0 {
+            out.push_str(&format!("[offset:{:+}]\n", self.offset_ms));
+        }
+        for line in &self.lines {
+            let stamp = format_lrc_timestamp(line.timestamp);
+            out.push_str(&format!("[{}]{}\n", stamp, line.text));
+            if let Some(ref translation) = line.translation {
+                out.push_str(&format!("[{}]{}\n", stamp, translation));
+            }
+        }
+        out
+    }
+
+    /// Write the serialized LRC as the song's `.lrc` file in the lyrics
+    /// directory, creating that directory if needed. Returns the path written.
+    pub fn save(&self, artist: &str, title: &str) -> std::io::Result<PathBuf> {
+        let path = lyrics_path(artist, title, "lrc");
+        std::fs::create_dir_all(lyrics_dir())?;
+        std::fs::write(&path, self.to_lrc_string())?;
+        Ok(path)
+    }
+}
+
+/// Format seconds as an LRC timestamp: `mm:ss.xx` (hundredths).
+pub fn format_lrc_timestamp(seconds: f64) -> String {
+    let centis = (seconds.max(0.0) * 100.0).round() as u64;
+    let mins = centis / 6000;
+    let secs = (centis % 6000) / 100;
+    let hundredths = centis % 100;
+    format!("{:02}:{:02}.{:02}", mins, secs, hundredths)
+}
+
+/// Returns the lyrics directory: ~/Music/Lyrics/
+pub fn lyrics_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join("Music").join("Lyrics")
+}
+
+/// Path of a lyrics file for a song: `~/Music/Lyrics/{Artist} - {Title}.{ext}`
+pub fn lyrics_path(artist: &str, title: &str, ext: &str) -> PathBuf {
+    lyrics_dir().join(format!("{}.{}", lyrics_stem(artist, title), ext))
+}
+
+/// File name stem for a song's lyrics: `{Artist} - {Title}`, with each tag
+/// made safe to use as part of a single file name.
+fn lyrics_stem(artist: &str, title: &str) -> String {
+    format!("{} - {}", sanitize_name(artist), sanitize_name(title))
+}
+
+/// Replace path separators and NULs in a tag, and refuse `.`/`..`, so tags
+/// like "AC/DC" can't leave the lyrics directory.
+fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c == '/' || c == '\0' { '_' } else { c })
+        .collect();
+    if name == "." || name == ".." { "_".to_string() } else { name }
+}
+
+/// Lyrics file extensions Bard can read, in lookup priority order.
+pub const LYRICS_EXTENSIONS: [&str; 4] = ["lrc", "ttml", "vtt", "srt"];
+
+/// Load synced lyrics from any supported format, chosen by file extension.
+pub fn load_lyrics_file<P: AsRef<Path>>(path: P) -> Option<LRCParser> {
+    let path = path.as_ref();
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    if ext == "lrc" {
+        return LRCParser::from_file(path);
+    }
+    let text = std::fs::read_to_string(path).ok()?;
+    let lines = match ext.as_str() {
+        "srt" => lyrics_formats::parse_srt(&text),
+        "vtt" => lyrics_formats::parse_vtt(&text),
+        "ttml" => lyrics_formats::parse_ttml(&text),
+        _ => return None,
+    };
+    if lines.is_empty() { None } else { Some(LRCParser::from_lines(lines)) }
+}
+
+/// The song's main lyrics file: `{Artist} - {Title}.{ext}` for the first
+/// extension in `LYRICS_EXTENSIONS` that exists.
+pub fn find_lyrics_file(artist: &str, title: &str) -> Option<PathBuf> {
+    LYRICS_EXTENSIONS
+        .iter()
+        .map(|ext| lyrics_path(artist, title, ext))
+        .find(|path| path.exists())
+}
+
+/// Secondary lyrics for a song: `{Artist} - {Title}.{lang}.{ext}`, such as
+/// `.en.lrc` or `.romaji.srt`. The first match in name order wins.
+pub fn translation_path(artist: &str, title: &str) -> Option<PathBuf> {
+    let prefix = format!("{}.", lyrics_stem(artist, title));
+    let mut found: Vec<PathBuf> = std::fs::read_dir(lyrics_dir())
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix(&prefix)
+                .and_then(|rest| rest.rsplit_once('.'))
+                .is_some_and(|(lang, ext)| {
+                    !lang.is_empty() && !lang.contains('.') && LYRICS_EXTENSIONS.contains(&ext)
+                })
+        })
+        .map(|entry| entry.path())
+        .collect();
+    found.sort();
+    found.into_iter().next()
+}
+
+/// Per-song offset overrides: `~/.local/share/bard/lyrics_offsets`,
+/// one `{offset_ms}\t{Artist} - {Title}` entry per line.
+fn offsets_file() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("bard")
+        .join("lyrics_offsets")
+}
+
+fn read_offsets() -> Vec<(i64, String)> {
+    let contents = std::fs::read_to_string(offsets_file()).unwrap_or_default();
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (ms, key) = line.split_once('\t')?;
+            Some((ms.parse().ok()?, key.to_string()))
+        })
+        .collect()
+}
+
+/// Saved offset override for a song, if the user has adjusted it.
+pub fn load_offset(artist: &str, title: &str) -> Option<i64> {
+    let key = format!("{} - {}", artist, title);
+    read_offsets().into_iter().find(|(_, k)| *k == key).map(|(ms, _)| ms)
+}
+
+/// Store the offset override for a song (it takes precedence over any `[offset:]` tag).
+pub fn save_offset(artist: &str, title: &str, offset_ms: i64) -> std::io::Result<()> {
+    write_offset(artist, title, Some(offset_ms))
+}
+
+/// Forget the offset override for a song, e.g. after it has been re-synced.
+pub fn clear_offset(artist: &str, title: &str) -> std::io::Result<()> {
+    write_offset(artist, title, None)
+}
+
+fn write_offset(artist: &str, title: &str, offset_ms: Option<i64>) -> std::io::Result<()> {
+    let key = format!("{} - {}", artist, title);
+    let mut entries = read_offsets();
+    let before = entries.len();
+    entries.retain(|(_, k)| *k != key);
+    match offset_ms {
+        Some(ms) => entries.push((ms, key)),
+        // Nothing stored for this song, so nothing to rewrite
+        None if entries.len() == before => return Ok(()),
+        None => {}
+    }
+    let path = offsets_file();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let contents: String = entries.iter().map(|(ms, k)| format!("{}\t{}\n", ms, k)).collect();
+    std::fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(timestamp: f64, text: &str) -> LyricLine {
+        LyricLine { timestamp, text: text.to_string(), translation: None, words: Vec::new() }
+    }
+
+    #[test]
+    fn lrc_round_trip() {
+        let mut lines = vec![line(1.5, "First"), line(65.25, "Second")];
+        lines[1].translation = Some("Zweite".to_string());
+        let lrc = LRCParser {
+            lines,
+            tags: vec![("ar".to_string(), "Artist".to_string()), ("ti".to_string(), "Title".to_string())],
+            offset_ms: -200,
+        };
+        let text = lrc.to_lrc_string();
+        assert_eq!(
+            text,
+            "[ar:Artist]\n[ti:Title]\n[offset:-200]\n[00:01.50]First\n[01:05.25]Second\n[01:05.25]Zweite\n"
+        );
+
+        let parsed = LRCParser::parse(&text).unwrap();
+        assert_eq!(parsed.tags, lrc.tags);
+        assert_eq!(parsed.offset_ms, -200);
+        assert_eq!(parsed.lines.len(), 2);
+        assert_eq!(parsed.lines[1].timestamp, 65.25);
+        assert_eq!(parsed.lines[1].translation.as_deref(), Some("Zweite"));
+        assert_eq!(parsed.to_lrc_string(), text);
+    }
+
+    #[test]
+    fn attach_translation_matches_nearby_lines() {
+        let mut main = LRCParser::from_lines(vec![line(1.0, "One"), line(5.0, "Two"), line(9.0, "Three")]);
+        let secondary = LRCParser::from_lines(vec![line(1.2, "Uno"), line(5.0, ""), line(6.0, "Dos")]);
+        main.attach_translation(&secondary);
+        let translations: Vec<Option<&str>> = main.lines.iter().map(|l| l.translation.as_deref()).collect();
+        assert_eq!(translations, vec![Some("Uno"), None, None]);
+    }
+
+    #[test]
+    fn lyrics_names_stay_in_the_lyrics_directory() {
+        let path = lyrics_path("AC/DC", "../../../.config/foo", "lrc");
+        assert_eq!(path.parent(), Some(lyrics_dir().as_path()));
+        assert_eq!(lyrics_path("..", "x\0y", "lrc").file_name().unwrap(), "_ - x_y.lrc");
+    }
 }
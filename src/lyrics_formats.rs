@@ -0,0 +1,273 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::lyrics::{LyricLine, LyricWord};
+
+/// Gaps between cues longer than this get an empty line so the highlight clears.
+const GAP_SECS: f64 = 1.0;
+
+static TTML_P: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<p\b([^>]*)>(.*?)</p>").unwrap());
+static TTML_BEGIN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\bbegin="([^"]+)""#).unwrap());
+static TTML_END: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\bend="([^"]+)""#).unwrap());
+static TTML_ROLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<span\b[^>]*ttm:role="x-(?:translation|roman)"[^>]*>(.*?)</span>"#).unwrap()
+});
+static TTML_LEAF_SPAN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<span\b([^>]*\bbegin="[^"]+"[^>]*)>([^<]*)</span>(\s?)"#).unwrap());
+static INLINE_TIMESTAMP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(\d{1,2}:\d{2}(?::\d{2})?[.,]\d{1,3})>").unwrap());
+static MARKUP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>|\{[^}]*\}").unwrap());
+
+struct Cue {
+    start: f64,
+    end: f64,
+    text: String,
+    translation: Option<String>,
+    words: Vec<LyricWord>,
+}
+
+/// Parse SubRip (`.srt`) subtitles into lyric lines.
+pub fn parse_srt(text: &str) -> Vec<LyricLine> {
+    cues_to_lines(blocks(text).into_iter().filter_map(parse_cue).collect())
+}
+
+/// Parse WebVTT (`.vtt`) subtitles into lyric lines. The `WEBVTT` header and
+/// NOTE, STYLE and REGION blocks are skipped; inline cue timestamps
+/// (`<00:12.500>word`) become per-word karaoke timing.
+pub fn parse_vtt(text: &str) -> Vec<LyricLine> {
+    let cues = blocks(text)
+        .into_iter()
+        .filter(|block| {
+            let keyword = block[0].split_whitespace().next().unwrap_or("");
+            !matches!(keyword, "WEBVTT" | "NOTE" | "STYLE" | "REGION")
+        })
+        .filter_map(parse_cue)
+        .collect();
+    cues_to_lines(cues)
+}
+
+/// Parse TTML (`.ttml`, Apple-style) lyrics. Each `<p>` is a line; timed
+/// `<span>`s inside it become per-syllable timing, and spans with
+/// `ttm:role="x-translation"` or `x-roman` become the line's translation.
+pub fn parse_ttml(text: &str) -> Vec<LyricLine> {
+    let mut cues = Vec::new();
+    for p in TTML_P.captures_iter(text) {
+        let attrs = &p[1];
+        let mut inner = p[2].to_string();
+        let Some(start) = TTML_BEGIN.captures(attrs).and_then(|c| parse_clock(&c[1])) else {
+            continue;
+        };
+        let end = TTML_END.captures(attrs).and_then(|c| parse_clock(&c[1])).unwrap_or(start);
+
+        // Pull the translation/romanization out before reading the sung text
+        let mut translation = None;
+        if let Some(c) = TTML_ROLE.captures(&inner) {
+            let t = clean_text(&c[1]);
+            if !t.is_empty() {
+                translation = Some(t);
+            }
+            inner = TTML_ROLE.replace_all(&inner, "").to_string();
+        }
+
+        let words: Vec<LyricWord> = TTML_LEAF_SPAN
+            .captures_iter(&inner)
+            .filter_map(|c| {
+                let start = TTML_BEGIN.captures(&c[1]).and_then(|b| parse_clock(&b[1]))?;
+                let text = format!("{}{}", decode_entities(&c[2]), if c[3].is_empty() { "" } else { " " });
+                Some(LyricWord { start, text })
+            })
+            .collect();
+
+        let text = clean_text(&inner);
+        cues.push(Cue { start, end, text, translation, words });
+    }
+
+    cues_to_lines(cues)
+}
+
+/// Parse one SRT/VTT cue block: an optional index or identifier, the
+/// `start --> end` line, then the text. Blocks without timing give `None`.
+fn parse_cue(block: Vec<&str>) -> Option<Cue> {
+    let mut lines = block.into_iter().skip_while(|l| !l.contains("-->"));
+    let (start_s, rest) = lines.next()?.split_once("-->")?;
+    // VTT cue settings follow the end time ("00:04.000 align:start")
+    let end_s = rest.split_whitespace().next().unwrap_or("");
+    let start = parse_clock(start_s.trim())?;
+    let end = parse_clock(end_s)?;
+
+    let raw: Vec<&str> = lines.map(str::trim).filter(|l| !l.is_empty()).collect();
+    let raw = raw.join(" ");
+
+    // Inline timestamps split the cue into timed words
+    let mut words = Vec::new();
+    if INLINE_TIMESTAMP.is_match(&raw) {
+        let mut word_start = start;
+        let mut last = 0;
+        for m in INLINE_TIMESTAMP.captures_iter(&raw) {
+            let whole = m.get(0).unwrap();
+            let seg = clean_text(&raw[last..whole.start()]);
+            if !seg.is_empty() {
+                words.push(LyricWord { start: word_start, text: with_trailing_space(&raw[last..whole.start()], seg) });
+            }
+            word_start = parse_clock(&m[1]).unwrap_or(word_start);
+            last = whole.end();
+        }
+        let seg = clean_text(&raw[last..]);
+        if !seg.is_empty() {
+            words.push(LyricWord { start: word_start, text: seg });
+        }
+    }
+
+    Some(Cue { start, end, text: clean_text(&raw), translation: None, words })
+}
+
+/// Split subtitle text into its blank-line separated blocks. `lines` copes
+/// with CRLF files, and a separator line may hold stray whitespace.
+fn blocks(text: &str) -> Vec<Vec<&str>> {
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(std::mem::take(&mut block));
+            }
+        } else {
+            block.push(line);
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// Keep the separating space after a word so the words join back into the line.
+fn with_trailing_space(raw: &str, cleaned: String) -> String {
+    if raw.ends_with(char::is_whitespace) { format!("{} ", cleaned) } else { cleaned }
+}
+
+/// Turn cues into lyric lines, inserting an empty line wherever there is a
+/// noticeable gap so nothing stays highlighted through instrumentals.
+fn cues_to_lines(mut cues: Vec<Cue>) -> Vec<LyricLine> {
+    cues.sort_by(|a, b| a.start.total_cmp(&b.start));
+    let mut lines = Vec::with_capacity(cues.len());
+    for i in 0..cues.len() {
+        let cue = &cues[i];
+        lines.push(LyricLine {
+            timestamp: cue.start,
+            text: cue.text.clone(),
+            translation: cue.translation.clone(),
+            words: cue.words.clone(),
+        });
+
+        let next_start = cues.get(i + 1).map(|n| n.start);
+        if cue.end > cue.start && next_start.is_none_or(|n| n - cue.end > GAP_SECS) {
+            lines.push(LyricLine { timestamp: cue.end, text: String::new(), translation: None, words: Vec::new() });
+        }
+    }
+    lines
+}
+
+/// Parse the clock formats used by SRT, VTT and TTML:
+/// `hh:mm:ss,ttt`, `hh:mm:ss.ttt`, `mm:ss.ttt`, `12.5s`, `12500ms` and bare seconds.
+/// `NaN` and infinities are rejected.
+fn parse_clock(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let secs = if let Some(ms) = s.strip_suffix("ms") {
+        ms.parse::<f64>().ok()? / 1000.0
+    } else if let Some(secs) = s.strip_suffix('s') {
+        secs.parse().ok()?
+    } else {
+        let mut total = 0.0;
+        for part in s.replace(',', ".").split(':') {
+            total = total * 60.0 + part.parse::<f64>().ok()?;
+        }
+        total
+    };
+    secs.is_finite().then_some(secs)
+}
+
+/// Strip markup (`<i>`, `<c.red>`, `{\an8}`), decode entities and collapse whitespace.
+fn clean_text(s: &str) -> String {
+    let stripped = MARKUP.replace_all(s, "");
+    decode_entities(&stripped).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: &[LyricLine]) -> Vec<(f64, &str)> {
+        lines.iter().map(|l| (l.timestamp, l.text.as_str())).collect()
+    }
+
+    #[test]
+    fn srt_with_crlf_and_padded_separators() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:03,000\r\nHello <i>world</i>\r\n  \r\n\
+                   2\r\n00:00:03,500 --> 00:00:04,000\r\nFish &amp; chips\r\n";
+        let lines = parse_srt(srt);
+        assert_eq!(texts(&lines), vec![(1.0, "Hello world"), (3.5, "Fish & chips"), (4.0, "")]);
+    }
+
+    #[test]
+    fn vtt_skips_header_and_note_blocks() {
+        let vtt = "WEBVTT - lyrics\n\n\
+                   NOTE 00:00:00.000 --> 00:00:05.000 is not a cue\n\n\
+                   STYLE\n::cue { color: red }\n\n\
+                   intro\n00:01.000 --> 00:02.000 align:start\nFirst\n";
+        let lines = parse_vtt(vtt);
+        assert_eq!(texts(&lines), vec![(1.0, "First"), (2.0, "")]);
+    }
+
+    #[test]
+    fn vtt_inline_timestamps_become_words() {
+        let vtt = "WEBVTT\n\n00:01.000 --> 00:03.000\nHel<00:01.500>lo <00:02.000>there\n";
+        let lines = parse_vtt(vtt);
+        assert_eq!(lines[0].text, "Hello there");
+        let words: Vec<(f64, &str)> = lines[0].words.iter().map(|w| (w.start, w.text.as_str())).collect();
+        assert_eq!(words, vec![(1.0, "Hel"), (1.5, "lo "), (2.0, "there")]);
+    }
+
+    #[test]
+    fn ttml_translation_span_is_split_off() {
+        let ttml = r#"<tt><body><div>
+            <p begin="1.0s" end="2.0s"><span begin="1.0s">Hi</span> <span begin="1.5s">you</span><span ttm:role="x-translation">Salut</span></p>
+            <p begin="00:02.000" end="00:04.000">Plain</p>
+        </div></body></tt>"#;
+        let lines = parse_ttml(ttml);
+        assert_eq!(texts(&lines), vec![(1.0, "Hi you"), (2.0, "Plain"), (4.0, "")]);
+        assert_eq!(lines[0].translation.as_deref(), Some("Salut"));
+        assert_eq!(lines[0].words.len(), 2);
+        assert_eq!(lines[0].words[1].start, 1.5);
+    }
+
+    #[test]
+    fn gap_line_only_after_long_pauses() {
+        let srt = "00:00:01,000 --> 00:00:02,000\nA\n\n\
+                   00:00:02,500 --> 00:00:03,000\nB\n\n\
+                   00:00:10,000 --> 00:00:11,000\nC\n";
+        let lines = parse_srt(srt);
+        assert_eq!(
+            texts(&lines),
+            vec![(1.0, "A"), (2.5, "B"), (3.0, ""), (10.0, "C"), (11.0, "")]
+        );
+    }
+
+    #[test]
+    fn non_finite_timestamps_are_dropped() {
+        let srt = "NaN --> 00:00:01,000\nbad\n\n00:00:02,000 --> 00:00:03,000\ngood\n";
+        assert_eq!(texts(&parse_srt(srt)), vec![(2.0, "good"), (3.0, "")]);
+        assert!(parse_ttml(r#"<p begin="nan" end="inf">bad</p>"#).is_empty());
+    }
+}
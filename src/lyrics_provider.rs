@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::lyrics;
+
+/// Public LRCLIB instance, used when `[lyrics] lrclib_url` isn't set.
+pub const DEFAULT_LRCLIB_URL: &str = "https://lrclib.net";
+
+/// Songs whose duration differs by more than this are treated as a different recording.
+const DURATION_TOLERANCE_SECS: f64 = 2.0;
+
+/// How long a song every provider came up empty for is left alone before asking again.
+const MISS_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// (artist, title) of recent misses, and when they were recorded.
+static MISSES: LazyLock<Mutex<HashMap<(String, String), Instant>>> = LazyLock::new(Default::default);
+
+/// What we know about the song when asking a provider for lyrics.
+#[derive(Clone, Debug)]
+pub struct LyricsQuery {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    pub duration: Option<f64>,
+}
+
+/// Lyrics returned by a provider: LRC text, or plain lines when no synced version exists.
+#[derive(Clone, Debug)]
+pub enum FetchedLyrics {
+    Synced(String),
+    Plain(String),
+}
+
+/// An online source of lyrics.
+pub trait LyricsProvider: Send {
+    fn name(&self) -> &str;
+
+    /// Look up lyrics for a song. `Ok(None)` means the provider has nothing for it.
+    fn fetch(&self, query: &LyricsQuery) -> Result<Option<FetchedLyrics>>;
+}
+
+/// Client for the LRCLIB API (`/api/get` and `/api/search`), or any server
+/// that speaks it, such as a local mirror.
+pub struct LrclibProvider {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LrclibTrack {
+    duration: Option<f64>,
+    #[serde(default)]
+    instrumental: bool,
+    plain_lyrics: Option<String>,
+    synced_lyrics: Option<String>,
+}
+
+impl LrclibProvider {
+    pub fn new(base_url: &str) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(10))
+            .user_agent(concat!("Bard/", env!("CARGO_PKG_VERSION"), " (https://github.com/MaveDX/Bard)"))
+            .build();
+        Self { base_url: base_url.trim_end_matches('/').to_string(), agent }
+    }
+
+    /// Exact lookup by signature; LRCLIB matches duration within ±2 s.
+    fn get(&self, query: &LyricsQuery) -> Result<Option<LrclibTrack>> {
+        let mut request = self.agent
+            .get(&format!("{}/api/get", self.base_url))
+            .query("artist_name", &query.artist)
+            .query("track_name", &query.title);
+        if let Some(ref album) = query.album {
+            request = request.query("album_name", album);
+        }
+        if let Some(duration) = query.duration {
+            request = request.query("duration", &format!("{}", duration.round() as u64));
+        }
+
+        match request.call() {
+            Ok(response) => Ok(Some(response.into_json().context("invalid LRCLIB response")?)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Fuzzy search, keeping the result closest in duration (and preferring synced lyrics).
+    fn search(&self, query: &LyricsQuery) -> Result<Option<LrclibTrack>> {
+        let results: Vec<LrclibTrack> = self.agent
+            .get(&format!("{}/api/search", self.base_url))
+            .query("artist_name", &query.artist)
+            .query("track_name", &query.title)
+            .call()?
+            .into_json()
+            .context("invalid LRCLIB response")?;
+
+        let distance = |track: &LrclibTrack| match (query.duration, track.duration) {
+            (Some(want), Some(got)) => (want - got).abs(),
+            _ => 0.0,
+        };
+
+        Ok(results
+            .into_iter()
+            .filter(|t| distance(t) <= DURATION_TOLERANCE_SECS)
+            .min_by(|a, b| {
+                let synced = |t: &LrclibTrack| t.synced_lyrics.is_none() as u8;
+                synced(a).cmp(&synced(b)).then(distance(a).partial_cmp(&distance(b)).unwrap())
+            }))
+    }
+}
+
+impl LyricsProvider for LrclibProvider {
+    fn name(&self) -> &str {
+        "LRCLIB"
+    }
+
+    fn fetch(&self, query: &LyricsQuery) -> Result<Option<FetchedLyrics>> {
+        let track = match self.get(query)? {
+            Some(track) => Some(track),
+            None => self.search(query)?,
+        };
+        let Some(track) = track else { return Ok(None) };
+        if track.instrumental {
+            return Ok(None);
+        }
+
+        let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty());
+        Ok(non_empty(track.synced_lyrics)
+            .map(FetchedLyrics::Synced)
+            .or_else(|| non_empty(track.plain_lyrics).map(FetchedLyrics::Plain)))
+    }
+}
+
+/// Providers enabled in the config, in lookup order. Empty unless the user
+/// opted in with `[lyrics] online = true`, so nothing touches the network by default.
+pub fn providers(config: &Config) -> Vec<Box<dyn LyricsProvider>> {
+    if !config.get_bool("lyrics", "online", false) {
+        return Vec::new();
+    }
+    let base_url = config.get("lyrics", "lrclib_url").unwrap_or(DEFAULT_LRCLIB_URL);
+    vec![Box::new(LrclibProvider::new(base_url))]
+}
+
+/// Ask each provider in turn and save the first hit to the lyrics folder:
+/// synced lyrics as `{Artist} - {Title}.lrc`, plain ones as `.txt` (ready for Ctrl+L syncing).
+/// Blocks on network I/O, so call it from a background thread. A song no
+/// provider has lyrics for isn't asked about again for `MISS_TTL`; lookups
+/// that failed (network errors and the like) are retried next time.
+pub fn fetch_and_save(providers: &[Box<dyn LyricsProvider>], query: &LyricsQuery) -> Option<PathBuf> {
+    let key = (query.artist.clone(), query.title.clone());
+    let mut misses = MISSES.lock().unwrap_or_else(|e| e.into_inner());
+    misses.retain(|_, at| at.elapsed() < MISS_TTL);
+    if misses.contains_key(&key) {
+        return None;
+    }
+    drop(misses);
+
+    let mut failed = false;
+    for provider in providers {
+        let fetched = match provider.fetch(query) {
+            Ok(Some(fetched)) => fetched,
+            Ok(None) => continue,
+            Err(e) => {
+                log::warn!("{} lyrics lookup failed: {:#}", provider.name(), e);
+                failed = true;
+                continue;
+            }
+        };
+
+        let (ext, text) = match fetched {
+            FetchedLyrics::Synced(text) => ("lrc", text),
+            FetchedLyrics::Plain(text) => ("txt", text),
+        };
+        let path = lyrics::lyrics_path(&query.artist, &query.title, ext);
+        let saved = std::fs::create_dir_all(lyrics::lyrics_dir())
+            .and_then(|_| std::fs::write(&path, text));
+        match saved {
+            Ok(()) => {
+                log::info!("Saved lyrics from {} to {}", provider.name(), path.display());
+                return Some(path);
+            }
+            Err(e) => {
+                log::warn!("Failed to save lyrics to {}: {}", path.display(), e);
+                failed = true;
+            }
+        }
+    }
+    if !failed {
+        MISSES.lock().unwrap_or_else(|e| e.into_inner()).insert(key, Instant::now());
+    }
+    None
+}
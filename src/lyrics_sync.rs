@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use crate::lyrics::{LRCParser, LyricLine};
+
+/// Nudge step for adjusting a stamp, in seconds (±100 ms).
+pub const NUDGE_STEP: f64 = 0.1;
+
+/// Tap-to-sync session: turns plain lyrics into LRC by stamping the
+/// playback time on each line as it is sung.
+pub struct LyricsSync {
+    lines: Vec<String>,
+    stamps: Vec<Option<f64>>,
+    /// Index of the next line to be stamped
+    cursor: usize,
+}
+
+impl LyricsSync {
+    /// Build a session from plain text, one lyric per non-empty line.
+    pub fn from_text(text: &str) -> Self {
+        let lines: Vec<String> = text
+            .lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect();
+        let stamps = vec![None; lines.len()];
+        Self { lines, stamps, cursor: 0 }
+    }
+
+    /// Load plain lyrics from a `.txt` file.
+    pub fn from_plain_file<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let text = std::fs::read_to_string(path).ok()?;
+        let sync = Self::from_text(&text);
+        if sync.is_empty() { None } else { Some(sync) }
+    }
+
+    /// Re-sync an existing LRC: keep its text, drop its timing.
+    pub fn from_lrc(lrc: &LRCParser) -> Option<Self> {
+        let text: Vec<&str> = lrc.lines.iter().map(|l| l.text.as_str()).collect();
+        let sync = Self::from_text(&text.join("\n"));
+        if sync.is_empty() { None } else { Some(sync) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.cursor >= self.lines.len()
+    }
+
+    /// Stamp `time` on the line under the cursor and advance.
+    /// Returns the index of the stamped line.
+    pub fn stamp(&mut self, time: f64) -> Option<usize> {
+        if self.is_complete() {
+            return None;
+        }
+        // Never stamp earlier than the previous line — keeps the LRC monotonic
+        let floor = self.cursor.checked_sub(1)
+            .and_then(|i| self.stamps[i])
+            .unwrap_or(0.0);
+        let idx = self.cursor;
+        self.stamps[idx] = Some(time.max(floor));
+        self.cursor += 1;
+        Some(idx)
+    }
+
+    /// Clear the most recent stamp and move the cursor back onto that line.
+    pub fn unstamp(&mut self) -> Option<usize> {
+        let idx = self.cursor.checked_sub(1)?;
+        self.stamps[idx] = None;
+        self.cursor = idx;
+        Some(idx)
+    }
+
+    /// Shift the most recent stamp by `delta` seconds, clamped between its neighbours.
+    pub fn nudge(&mut self, delta: f64) -> Option<(usize, f64)> {
+        let idx = self.cursor.checked_sub(1)?;
+        let current = self.stamps[idx]?;
+        let lower = idx.checked_sub(1).and_then(|i| self.stamps[i]).unwrap_or(0.0);
+        let upper = self.stamps.get(idx + 1).copied().flatten().unwrap_or(f64::MAX);
+        let new_time = (current + delta).clamp(lower, upper);
+        self.stamps[idx] = Some(new_time);
+        Some((idx, new_time))
+    }
+
+    /// All lines for display; unstamped lines carry a zero timestamp.
+    pub fn display_lines(&self) -> Vec<LyricLine> {
+        self.lines.iter().zip(&self.stamps)
+            .map(|(text, stamp)| LyricLine { timestamp: stamp.unwrap_or(0.0), text: text.clone(), translation: None, words: Vec::new() })
+            .collect()
+    }
+
+    /// LRC built from the stamped lines so far. Indices line up with
+    /// `display_lines`, so it can drive the normal lyrics highlight.
+    pub fn preview(&self) -> LRCParser {
+        let lines = self.lines.iter().zip(&self.stamps)
+            .map_while(|(text, stamp)| stamp.map(|t| LyricLine { timestamp: t, text: text.clone(), translation: None, words: Vec::new() }))
+            .collect();
+        LRCParser { lines, tags: Vec::new(), offset_ms: 0 }
+    }
+
+    /// Final LRC with artist/title tags, or None until every line is stamped.
+    pub fn to_lrc(&self, artist: &str, title: &str) -> Option<LRCParser> {
+        if !self.is_complete() {
+            return None;
+        }
+        let mut lrc = self.preview();
+        lrc.tags = vec![
+            ("ar".to_string(), artist.to_string()),
+            ("ti".to_string(), title.to_string()),
+            ("by".to_string(), "Bard".to_string()),
+        ];
+        Some(lrc)
+    }
+}
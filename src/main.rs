@@ -3,10 +3,22 @@ use gtk::Application;
 
 mod cava;
 mod color_extractor;
+mod config;
+mod decode;
+mod loudness;
 mod lyrics;
+mod lyrics_formats;
+mod lyrics_provider;
+mod lyrics_sync;
 mod mpd_client;
+mod sections;
+mod spectrogram;
+mod track_analysis;
 mod ui;
+mod visualizer;
 mod waveform;
+mod waveform_cache;
+mod waveform_jobs;
 
 use ui::MusicPlayerWindow;
 
@@ -1,4 +1,4 @@
-use mpd::{Client, Song, Status};
+use mpd::{Client, Query, Song, Status, Term};
 use std::net::TcpStream;
 use std::time::Duration;
 use std::ops::RangeFull;
@@ -60,6 +60,11 @@ impl MPDClient {
         Ok(self.client.volume(volume)?)
     }
 
+    /// The song at queue position `pos`, if there is one.
+    pub fn song_at(&mut self, pos: u32) -> Result<Option<Song>> {
+        Ok(self.client.songs(pos)?.into_iter().next())
+    }
+
     pub fn get_queue(&mut self) -> Result<Vec<Song>> {
         Ok(self.client.queue()?)
     }
@@ -80,6 +85,24 @@ impl MPDClient {
         Ok(self.client.listall()?)
     }
 
+    /// Songs from the same album as `song`: the same Album tag, and the same
+    /// AlbumArtist (or Artist, when it has no AlbumArtist). A song without an
+    /// Album tag is an album of its own.
+    pub fn album_songs(&mut self, song: &Song) -> Result<Vec<Song>> {
+        let tag = |name: &str| song.tags.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+        let Some(album) = tag("Album") else {
+            return Ok(vec![song.clone()]);
+        };
+        let mut query = Query::new();
+        query.and(Term::Tag("Album".into()), album);
+        if let Some(album_artist) = tag("AlbumArtist") {
+            query.and(Term::Tag("AlbumArtist".into()), album_artist);
+        } else if let Some(artist) = song.artist.as_deref() {
+            query.and(Term::Tag("Artist".into()), artist);
+        }
+        Ok(self.client.find(&query, None::<(u32, u32)>)?)
+    }
+
     pub fn clear(&mut self) -> Result<()> {
         Ok(self.client.clear()?)
     }
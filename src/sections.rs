@@ -0,0 +1,160 @@
+use crate::waveform::PeakPair;
+
+/// Bins quieter than this (RMS, dBFS) count as silence, unless the whole
+/// track is so quiet that a threshold relative to its loud parts is lower.
+const SILENCE_DB: f64 = -50.0;
+const SILENCE_BELOW_LOUD_DB: f64 = 35.0;
+
+/// Level change (dB) between the stretches before and after a point for it
+/// to count as a section boundary.
+const CHANGE_DB: f64 = 6.0;
+
+/// Boundaries kept per track, strongest first.
+const MAX_MARKERS: usize = 8;
+
+/// Tracks with fewer bins than this aren't analysed.
+const MIN_BINS: usize = 64;
+
+/// Silence shorter than this (seconds) isn't worth skipping.
+pub const MIN_SKIPPABLE_SILENCE: f64 = 2.0;
+
+/// What changes at a section boundary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SectionKind {
+    /// First rise in level near the start
+    IntroEnd,
+    /// Any other sharp rise
+    Drop,
+    /// A sharp fall mid-track
+    Breakdown,
+    /// Last fall near the end
+    Outro,
+}
+
+impl SectionKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            SectionKind::IntroEnd => "End of intro",
+            SectionKind::Drop => "Drop",
+            SectionKind::Breakdown => "Breakdown",
+            SectionKind::Outro => "Outro",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SectionMarker {
+    /// 0.0–1.0 along the track
+    pub position: f64,
+    pub kind: SectionKind,
+}
+
+/// A track's silent edges and the boundaries between its louder and quieter
+/// parts, as fractions of its length.
+#[derive(Clone, Debug)]
+pub struct TrackSections {
+    /// Where the silence the track starts with ends (0.0 if none)
+    pub leading_silence: f64,
+    /// Where the silence the track ends with starts (1.0 if none)
+    pub trailing_silence: f64,
+    pub markers: Vec<SectionMarker>,
+}
+
+impl Default for TrackSections {
+    fn default() -> Self {
+        Self { leading_silence: 0.0, trailing_silence: 1.0, markers: Vec::new() }
+    }
+}
+
+impl TrackSections {
+    /// Find the silences and sections in a waveform profile's bins.
+    pub fn analyze(bins: &[PeakPair]) -> Self {
+        let n = bins.len();
+        if n < MIN_BINS {
+            return Self::default();
+        }
+        let energy: Vec<f64> = bins.iter().map(|b| (b.left * b.left + b.right * b.right) / 2.0).collect();
+        let levels: Vec<f64> = energy.iter().map(|&e| to_db(e)).collect();
+
+        let mut sorted = levels.clone();
+        sorted.sort_by(f64::total_cmp);
+        let loud = sorted[sorted.len() * 95 / 100];
+        let threshold = SILENCE_DB.min(loud - SILENCE_BELOW_LOUD_DB);
+        let (Some(first), Some(last)) = (
+            levels.iter().position(|&l| l > threshold),
+            levels.iter().rposition(|&l| l > threshold),
+        ) else {
+            // Silent throughout: nothing to mark or skip
+            return Self::default();
+        };
+
+        Self {
+            leading_silence: first as f64 / n as f64,
+            trailing_silence: (last + 1) as f64 / n as f64,
+            markers: boundaries(&energy, first, last + 1),
+        }
+    }
+}
+
+fn to_db(energy: f64) -> f64 {
+    10.0 * energy.max(1e-12).log10()
+}
+
+/// Section boundaries within `energy[start..end]`: points where the mean level
+/// of a stretch before differs from the stretch after by at least `CHANGE_DB`,
+/// and by more than at any other point within a stretch's length.
+fn boundaries(energy: &[f64], start: usize, end: usize) -> Vec<SectionMarker> {
+    let n = energy.len();
+    // A stretch is ~2.5% of the track (about 6 s of a four-minute song)
+    let window = (n / 40).max(8);
+    if end < start + 2 * window {
+        return Vec::new();
+    }
+
+    let mut prefix = vec![0.0; n + 1];
+    for (i, e) in energy.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + e;
+    }
+    let mean_db = |from: usize, to: usize| to_db((prefix[to] - prefix[from]) / (to - from) as f64);
+    let change: Vec<(usize, f64)> = (start + window..=end - window)
+        .map(|i| (i, mean_db(i, i + window) - mean_db(i - window, i)))
+        .collect();
+
+    // Ties go to the earliest point of a plateau
+    let mut peaks: Vec<(usize, f64)> = change
+        .iter()
+        .enumerate()
+        .filter(|&(k, &(_, c))| {
+            let around = &change[k.saturating_sub(window)..(k + window + 1).min(change.len())];
+            c.abs() >= CHANGE_DB
+                && around.iter().enumerate().all(|(m, &(_, d))| {
+                    d.abs() < c.abs() || (d.abs() == c.abs() && m + k.saturating_sub(window) >= k)
+                })
+        })
+        .map(|(_, &peak)| peak)
+        .collect();
+    peaks.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+    peaks.truncate(MAX_MARKERS);
+    peaks.sort_by_key(|&(i, _)| i);
+
+    let last_fall = peaks.iter().rposition(|&(_, c)| c < 0.0);
+    peaks
+        .iter()
+        .enumerate()
+        .map(|(k, &(i, c))| {
+            let position = i as f64 / n as f64;
+            let kind = if c > 0.0 {
+                if k == 0 && position < 0.3 {
+                    SectionKind::IntroEnd
+                } else {
+                    SectionKind::Drop
+                }
+            } else if Some(k) == last_fall && position > 0.7 {
+                SectionKind::Outro
+            } else {
+                SectionKind::Breakdown
+            };
+            SectionMarker { position, kind }
+        })
+        .collect()
+}
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+use crate::decode::PcmSink;
+use crate::waveform::WaveformColors;
+
+/// Time columns and frequency bands kept per track (and in the disk cache).
+pub const SPECTROGRAM_COLUMNS: usize = 1024;
+pub const SPECTROGRAM_BANDS: usize = 64;
+
+/// Samples per FFT frame; frames don't overlap (a column averages several).
+const FFT_SIZE: usize = 2048;
+
+/// Bands are spaced logarithmically over this range, in Hz.
+const MIN_FREQ: f64 = 40.0;
+const MAX_FREQ: f64 = 16000.0;
+
+/// Levels this far below the loudest cell (dB) are drawn transparent.
+const DYNAMIC_RANGE_DB: f64 = 70.0;
+
+/// Whole-track spectrogram at a fixed resolution, like `WaveformProfile`
+/// for the bars: decoded once, cached, and scaled to any size when drawn.
+#[derive(Clone, Debug)]
+pub struct Spectrogram {
+    /// Column by column, lowest band first; 0 is `DYNAMIC_RANGE_DB` below
+    /// the loudest cell and 255 the loudest
+    pub levels: Vec<u8>,
+}
+
+impl Spectrogram {
+    fn level(&self, column: usize, band: usize) -> u8 {
+        self.levels[column * SPECTROGRAM_BANDS + band]
+    }
+}
+
+/// Streaming spectrogram analysis; feed it through `decode::decode_stereo`
+/// (usually as a tap on the waveform decode).
+#[derive(Default)]
+pub struct SpectrogramAnalyzer {
+    fft: Option<Arc<dyn Fft<f32>>>,
+    window: Vec<f32>,
+    /// FFT bin range of each band at the current sample rate
+    bands: Vec<(usize, usize)>,
+    /// Mono samples of the frame being filled
+    samples: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    /// Band energies of every finished frame
+    frames: Vec<[f32; SPECTROGRAM_BANDS]>,
+}
+
+impl SpectrogramAnalyzer {
+    /// The spectrogram of everything fed in; None if not even one frame was.
+    pub fn finish(self) -> Option<Spectrogram> {
+        let n = self.frames.len();
+        if n == 0 {
+            return None;
+        }
+        // Mean energy per cell, in dB
+        let mut cells = Vec::with_capacity(SPECTROGRAM_COLUMNS * SPECTROGRAM_BANDS);
+        for column in 0..SPECTROGRAM_COLUMNS {
+            let start = (column * n / SPECTROGRAM_COLUMNS).min(n - 1);
+            let end = ((column + 1) * n / SPECTROGRAM_COLUMNS).clamp(start + 1, n);
+            let frames = &self.frames[start..end];
+            for band in 0..SPECTROGRAM_BANDS {
+                let energy = frames.iter().map(|f| f[band] as f64).sum::<f64>() / frames.len() as f64;
+                cells.push(10.0 * energy.max(1e-20).log10());
+            }
+        }
+        let loudest = cells.iter().copied().fold(f64::MIN, f64::max);
+        let floor = loudest - DYNAMIC_RANGE_DB;
+        let levels = cells
+            .iter()
+            .map(|db| ((db - floor) / DYNAMIC_RANGE_DB * 255.0).clamp(0.0, 255.0) as u8)
+            .collect();
+        Some(Spectrogram { levels })
+    }
+
+    fn analyze_frame(&mut self) {
+        let Some(ref fft) = self.fft else { return };
+        for ((out, sample), w) in self.spectrum.iter_mut().zip(&self.samples).zip(&self.window) {
+            *out = Complex::new(sample * w, 0.0);
+        }
+        fft.process(&mut self.spectrum);
+
+        let mut energies = [0.0; SPECTROGRAM_BANDS];
+        for (energy, &(lo, hi)) in energies.iter_mut().zip(&self.bands) {
+            if hi > lo {
+                *energy = self.spectrum[lo..hi].iter().map(|c| c.norm_sqr()).sum::<f32>() / (hi - lo) as f32;
+            }
+        }
+        self.frames.push(energies);
+        self.samples.clear();
+    }
+}
+
+impl PcmSink for SpectrogramAnalyzer {
+    fn begin(&mut self, sample_rate: u32, _total_frames: Option<u64>, _channels: usize) {
+        self.fft = Some(FftPlanner::new().plan_fft_forward(FFT_SIZE));
+        self.window = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+        self.spectrum = vec![Complex::default(); FFT_SIZE];
+        self.samples = Vec::with_capacity(FFT_SIZE);
+        self.frames.clear();
+
+        // Bands past Nyquist (e.g. from the 8 kHz ffmpeg fallback) stay empty
+        let nyquist_bin = FFT_SIZE / 2;
+        let bin_of = |freq: f64| ((freq * FFT_SIZE as f64 / sample_rate as f64).round() as usize).min(nyquist_bin);
+        self.bands = (0..SPECTROGRAM_BANDS)
+            .map(|band| {
+                let edge = |b: usize| MIN_FREQ * (MAX_FREQ / MIN_FREQ).powf(b as f64 / SPECTROGRAM_BANDS as f64);
+                let lo = bin_of(edge(band));
+                // Low bands are narrower than one FFT bin; give each at least one
+                let hi = bin_of(edge(band + 1)).max(lo + 1).min(nyquist_bin);
+                (lo, hi)
+            })
+            .collect();
+    }
+
+    fn frame(&mut self, left: f32, right: f32) {
+        if self.fft.is_none() {
+            return;
+        }
+        self.samples.push((left + right) / 2.0);
+        if self.samples.len() == FFT_SIZE {
+            self.analyze_frame();
+        }
+    }
+}
+
+/// Render `spectrogram` at one pixel per cell, low frequencies at the bottom.
+/// Quiet cells fade out to show the background; louder ones run from the
+/// waveform's unplayed colour through its played colour to white.
+pub fn render(spectrogram: &Spectrogram, colors: &WaveformColors) -> Option<cairo::ImageSurface> {
+    let (w, h) = (SPECTROGRAM_COLUMNS as i32, SPECTROGRAM_BANDS as i32);
+    let mut surface = cairo::ImageSurface::create(cairo::Format::ARgb32, w, h).ok()?;
+    let stride = surface.stride() as usize;
+    let (ur, ug, ub, _) = colors.unplayed;
+    let (pr, pg, pb, _) = colors.played;
+    {
+        let mut data = surface.data().ok()?;
+        for column in 0..SPECTROGRAM_COLUMNS {
+            for band in 0..SPECTROGRAM_BANDS {
+                let t = spectrogram.level(column, band) as f64 / 255.0;
+                let ((r, g, b), a) = if t < 0.5 {
+                    ((ur, ug, ub), t * 1.4)
+                } else {
+                    let s = (t - 0.5) * 2.0;
+                    let mix = |from: f64, to: f64| {
+                        let c = from + (to - from) * s.min(0.7) / 0.7;
+                        c + (1.0 - c) * (s - 0.7).max(0.0) / 0.3 * 0.8
+                    };
+                    ((mix(ur, pr), mix(ug, pg), mix(ub, pb)), 0.7 + s * 0.3)
+                };
+                // Cairo wants premultiplied BGRA (in native byte order)
+                let y = SPECTROGRAM_BANDS - 1 - band;
+                let idx = y * stride + column * 4;
+                let px = (a * 255.0) as u32;
+                let pixel = (px << 24)
+                    | (((r * a * 255.0) as u32) << 16)
+                    | (((g * a * 255.0) as u32) << 8)
+                    | ((b * a * 255.0) as u32);
+                data[idx..idx + 4].copy_from_slice(&pixel.to_ne_bytes());
+            }
+        }
+    }
+    surface.mark_dirty();
+    Some(surface)
+}
+
+/// A spectrogram with its rendering for the palette last drawn with, so it
+/// is only re-rendered when the album colours change.
+pub struct SpectrogramImage {
+    spectrogram: Spectrogram,
+    palette: Option<[(f64, f64, f64); 4]>,
+    surface: Option<cairo::ImageSurface>,
+}
+
+impl SpectrogramImage {
+    pub fn new(spectrogram: Spectrogram) -> Self {
+        Self { spectrogram, palette: None, surface: None }
+    }
+
+    pub fn surface(&mut self, palette: &[(f64, f64, f64); 4]) -> Option<&cairo::ImageSurface> {
+        if self.palette.as_ref() != Some(palette) {
+            self.palette = Some(*palette);
+            self.surface = render(&self.spectrogram, &WaveformColors::from_palette(palette));
+        }
+        self.surface.as_ref()
+    }
+}
+
+/// Draw the spectrogram stretched over the whole area, with the part not yet
+/// played dimmed and a playhead line, matching `draw_waveform`.
+pub fn draw_spectrogram(
+    cr: &cairo::Context,
+    surface: &cairo::ImageSurface,
+    position: f64,
+    w: f64,
+    h: f64,
+    colors: &WaveformColors,
+) {
+    if w <= 0.0 || h <= 0.0 {
+        return;
+    }
+    let cursor_x = position.clamp(0.0, 1.0) * w;
+
+    let _ = cr.save();
+    cr.scale(w / SPECTROGRAM_COLUMNS as f64, h / SPECTROGRAM_BANDS as f64);
+    if cr.set_source_surface(surface, 0.0, 0.0).is_ok() {
+        cr.source().set_filter(cairo::Filter::Good);
+        let _ = cr.paint();
+    }
+    let _ = cr.restore();
+
+    cr.set_source_rgba(0.0, 0.0, 0.0, 0.45);
+    cr.rectangle(cursor_x, 0.0, w - cursor_x, h);
+    let _ = cr.fill();
+
+    if position > 0.0 {
+        let (r, g, b, a) = colors.playhead;
+        cr.set_source_rgba(r, g, b, a);
+        cr.rectangle(cursor_x - 0.5, 0.0, 1.0, h);
+        let _ = cr.fill();
+    }
+}
@@ -0,0 +1,296 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+use crate::decode::PcmSink;
+
+/// Onset detection: spectral flux over short overlapping frames.
+const ONSET_FFT_SIZE: usize = 1024;
+const ONSET_HOP: usize = 512;
+
+/// Tempo search range, and the tempo the octave-error prior centres on.
+const MIN_BPM: f64 = 50.0;
+const MAX_BPM: f64 = 220.0;
+const PREFERRED_BPM: f64 = 120.0;
+
+/// Below this autocorrelation (relative to lag 0) there's no steady beat.
+const MIN_BEAT_STRENGTH: f64 = 0.15;
+
+/// Key detection: long frames for fine frequency resolution in the bass.
+const CHROMA_FFT_SIZE: usize = 8192;
+const CHROMA_MIN_FREQ: f64 = 65.0;
+const CHROMA_MAX_FREQ: f64 = 2100.0;
+
+/// Krumhansl–Kessler key profiles, starting from the tonic.
+const MAJOR_PROFILE: [f64; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+const MINOR_PROFILE: [f64; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+
+const PITCH_CLASSES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MusicalKey {
+    /// Pitch class of the tonic, 0 = C
+    pub tonic: u8,
+    pub minor: bool,
+}
+
+impl MusicalKey {
+    /// e.g. "A minor"
+    pub fn name(&self) -> String {
+        let mode = if self.minor { "minor" } else { "major" };
+        format!("{} {}", PITCH_CLASSES[self.tonic as usize % 12], mode)
+    }
+
+    /// The compact form used in TKEY/INITIALKEY tags, e.g. "Am" or "F#".
+    pub fn tag_value(&self) -> String {
+        format!("{}{}", PITCH_CLASSES[self.tonic as usize % 12], if self.minor { "m" } else { "" })
+    }
+}
+
+/// Estimated tempo and key of a track; either can be missing (no steady
+/// beat, or nothing tonal to go on).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrackAnalysis {
+    pub bpm: Option<f64>,
+    pub key: Option<MusicalKey>,
+}
+
+impl TrackAnalysis {
+    /// For the player, e.g. "128 BPM · A minor"; None if nothing was found.
+    pub fn describe(&self) -> Option<String> {
+        let parts: Vec<String> = self.bpm
+            .map(|bpm| format!("{:.0} BPM", bpm))
+            .into_iter()
+            .chain(self.key.map(|key| key.name()))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+}
+
+/// Streaming tempo and key estimation; feed it through `decode::decode_stereo`
+/// (usually as a tap on the waveform decode).
+#[derive(Default)]
+pub struct TrackAnalyzer {
+    sample_rate: u32,
+    onset_fft: Option<Arc<dyn Fft<f32>>>,
+    chroma_fft: Option<Arc<dyn Fft<f32>>>,
+    onset_window: Vec<f32>,
+    chroma_window: Vec<f32>,
+    /// Mono samples not yet consumed by each analysis
+    onset_samples: Vec<f32>,
+    chroma_samples: Vec<f32>,
+    /// Log magnitudes of the previous onset frame
+    previous: Vec<f32>,
+    /// Spectral flux per onset hop
+    onsets: Vec<f32>,
+    chroma: [f64; 12],
+}
+
+impl TrackAnalyzer {
+    pub fn finish(self) -> TrackAnalysis {
+        let onset_rate = self.sample_rate as f64 / ONSET_HOP as f64;
+        TrackAnalysis {
+            bpm: estimate_tempo(&self.onsets, onset_rate),
+            key: estimate_key(&self.chroma),
+        }
+    }
+
+    fn analyze_onset_frame(&mut self) {
+        let Some(ref fft) = self.onset_fft else { return };
+        let mut spectrum: Vec<Complex<f32>> = self.onset_samples[..ONSET_FFT_SIZE]
+            .iter()
+            .zip(&self.onset_window)
+            .map(|(s, w)| Complex::new(s * w, 0.0))
+            .collect();
+        fft.process(&mut spectrum);
+
+        // Sum of increases in log magnitude: large when something new starts
+        let mut flux = 0.0;
+        for (bin, prev) in spectrum[..ONSET_FFT_SIZE / 2].iter().zip(self.previous.iter_mut()) {
+            let level = (1.0 + 100.0 * bin.norm()).ln();
+            flux += (level - *prev).max(0.0);
+            *prev = level;
+        }
+        self.onsets.push(flux);
+        self.onset_samples.drain(..ONSET_HOP);
+    }
+
+    fn analyze_chroma_frame(&mut self) {
+        let Some(ref fft) = self.chroma_fft else { return };
+        let mut spectrum: Vec<Complex<f32>> = self.chroma_samples
+            .iter()
+            .zip(&self.chroma_window)
+            .map(|(s, w)| Complex::new(s * w, 0.0))
+            .collect();
+        fft.process(&mut spectrum);
+
+        let bin_hz = self.sample_rate as f64 / CHROMA_FFT_SIZE as f64;
+        for (i, bin) in spectrum[..CHROMA_FFT_SIZE / 2].iter().enumerate().skip(1) {
+            let freq = i as f64 * bin_hz;
+            if !(CHROMA_MIN_FREQ..=CHROMA_MAX_FREQ).contains(&freq) {
+                continue;
+            }
+            let midi = 69.0 + 12.0 * (freq / 440.0).log2();
+            let pitch_class = (midi.round() as i64).rem_euclid(12) as usize;
+            self.chroma[pitch_class] += bin.norm() as f64;
+        }
+        self.chroma_samples.clear();
+    }
+}
+
+impl PcmSink for TrackAnalyzer {
+    fn begin(&mut self, sample_rate: u32, _total_frames: Option<u64>, _channels: usize) {
+        let mut planner = FftPlanner::new();
+        *self = Self {
+            sample_rate,
+            onset_fft: Some(planner.plan_fft_forward(ONSET_FFT_SIZE)),
+            chroma_fft: Some(planner.plan_fft_forward(CHROMA_FFT_SIZE)),
+            onset_window: hann(ONSET_FFT_SIZE),
+            chroma_window: hann(CHROMA_FFT_SIZE),
+            previous: vec![0.0; ONSET_FFT_SIZE / 2],
+            ..Self::default()
+        };
+    }
+
+    fn frame(&mut self, left: f32, right: f32) {
+        if self.sample_rate == 0 {
+            return;
+        }
+        let mono = (left + right) / 2.0;
+        self.onset_samples.push(mono);
+        if self.onset_samples.len() == ONSET_FFT_SIZE {
+            self.analyze_onset_frame();
+        }
+        self.chroma_samples.push(mono);
+        if self.chroma_samples.len() == CHROMA_FFT_SIZE {
+            self.analyze_chroma_frame();
+        }
+    }
+}
+
+fn hann(size: usize) -> Vec<f32> {
+    (0..size)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / size as f32).cos())
+        .collect()
+}
+
+/// Tempo from the autocorrelation of the onset curve: the beat period is the
+/// lag at which onsets line up best, nudged towards `PREFERRED_BPM` so a
+/// half- or double-time reading only wins when it is clearly stronger.
+fn estimate_tempo(onsets: &[f32], rate: f64) -> Option<f64> {
+    // Only the peaks matter: subtract a local mean (about half a second)
+    let radius = (rate / 4.0).round().max(1.0) as usize;
+    let n = onsets.len();
+    let mut prefix = vec![0.0f64; n + 1];
+    for (i, &o) in onsets.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + o as f64;
+    }
+    let curve: Vec<f64> = (0..n)
+        .map(|i| {
+            let (lo, hi) = (i.saturating_sub(radius), (i + radius + 1).min(n));
+            (onsets[i] as f64 - (prefix[hi] - prefix[lo]) / (hi - lo) as f64).max(0.0)
+        })
+        .collect();
+
+    let min_lag = (60.0 * rate / MAX_BPM).floor().max(1.0) as usize;
+    let max_lag = (60.0 * rate / MIN_BPM).ceil() as usize;
+    if n < max_lag * 4 {
+        return None;
+    }
+    let energy: f64 = curve.iter().map(|c| c * c).sum();
+    if energy <= 0.0 {
+        return None;
+    }
+    let acf_at = |lag: usize| curve.iter().zip(&curve[lag..]).map(|(a, b)| a * b).sum::<f64>() / energy;
+    let acf: Vec<f64> = (0..=max_lag).map(acf_at).collect();
+
+    let weight = |lag: f64| {
+        let octaves = (60.0 * rate / lag / PREFERRED_BPM).log2();
+        (-0.5 * octaves * octaves).exp()
+    };
+    let best = (min_lag..=max_lag).max_by(|&a, &b| (acf[a] * weight(a as f64)).total_cmp(&(acf[b] * weight(b as f64))))?;
+    if acf[best] < MIN_BEAT_STRENGTH {
+        return None;
+    }
+
+    // The peak four beats out pins the period down four times as finely;
+    // interpolate between lags around it too
+    let beats = if best * 4 + 3 < n { 4 } else { 1 };
+    let around = best * beats;
+    let peak = (around - 2..=around + 2).max_by(|&a, &b| acf_at(a).total_cmp(&acf_at(b)))?;
+    let (l, c, r) = (acf_at(peak - 1), acf_at(peak), acf_at(peak + 1));
+    let denom = l - 2.0 * c + r;
+    let offset = if denom.abs() > f64::EPSILON { (0.5 * (l - r) / denom).clamp(-0.5, 0.5) } else { 0.0 };
+    let bpm = 60.0 * rate * beats as f64 / (peak as f64 + offset);
+    Some((bpm * 10.0).round() / 10.0)
+}
+
+/// The key whose profile correlates best with the track's pitch-class totals.
+fn estimate_key(chroma: &[f64; 12]) -> Option<MusicalKey> {
+    if chroma.iter().sum::<f64>() <= 0.0 {
+        return None;
+    }
+    let mut best: Option<(f64, MusicalKey)> = None;
+    for tonic in 0..12 {
+        for (profile, minor) in [(&MAJOR_PROFILE, false), (&MINOR_PROFILE, true)] {
+            let rotated: Vec<f64> = (0..12).map(|pc| chroma[(pc + tonic) % 12]).collect();
+            let score = correlation(&rotated, profile);
+            if best.is_none_or(|(s, _)| score > s) {
+                best = Some((score, MusicalKey { tonic: tonic as u8, minor }));
+            }
+        }
+    }
+    best.map(|(_, key)| key)
+}
+
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let (mean_a, mean_b) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+    let mut cov = 0.0;
+    let (mut var_a, mut var_b) = (0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a) * (x - mean_a);
+        var_b += (y - mean_b) * (y - mean_b);
+    }
+    if var_a <= 0.0 || var_b <= 0.0 {
+        return 0.0;
+    }
+    cov / (var_a * var_b).sqrt()
+}
+
+/// Write the tempo and key as TBPM/TKEY frames for MP3 or BPM/INITIALKEY
+/// Vorbis comments for FLAC, replacing older values. Missing values are
+/// left as they were.
+pub fn write_tags(path: &Path, analysis: &TrackAnalysis) -> Result<()> {
+    let bpm = analysis.bpm.map(|bpm| format!("{:.0}", bpm));
+    let key = analysis.key.map(|key| key.tag_value());
+
+    let ext = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+    match ext.as_deref() {
+        Some("mp3") => {
+            use id3::TagLike;
+            let mut tag = crate::loudness::read_id3(path)?;
+            for (frame, value) in [("TBPM", bpm), ("TKEY", key)] {
+                if let Some(value) = value {
+                    tag.set_text(frame, value);
+                }
+            }
+            crate::loudness::write_id3(&tag, path)?;
+        }
+        Some("flac") => {
+            let mut tag = metaflac::Tag::read_from_path(path)?;
+            for (name, value) in [("BPM", bpm), ("INITIALKEY", key)] {
+                if let Some(value) = value {
+                    tag.set_vorbis(name, vec![value]);
+                }
+            }
+            tag.save()?;
+        }
+        _ => bail!("writing tags to {} files isn't supported", ext.as_deref().unwrap_or("these")),
+    }
+    Ok(())
+}
@@ -7,18 +7,28 @@ use gtk::{
 use gdk;
 use gdk_pixbuf::Pixbuf;
 use glib;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::path::{Path, PathBuf};
 
-use crate::cava::CavaVisualizer;
 use crate::color_extractor::ColorExtractor;
-use crate::lyrics::LRCParser;
+use crate::config::Config;
+use crate::loudness::{self, LoudnessSummary, TrackLoudness};
+use crate::lyrics::{self, LRCParser, LyricLine};
+use crate::lyrics_provider::{self, LyricsQuery};
+use crate::lyrics_sync::{self, LyricsSync};
 use crate::mpd_client::{MPDClient, format_time};
-use crate::waveform::{self, WaveformData, PeakPair};
-
+use crate::sections::{self, TrackSections};
+use crate::spectrogram::{self, SpectrogramImage};
+use crate::track_analysis::{self, TrackAnalysis};
+use crate::visualizer::{BarRenderer, Visualizer, VisualizerLayout, VisualizerSettings};
+use crate::waveform::{self, PeakPair, PeakPyramid, WaveformColors, WaveformStyle};
+use crate::waveform_cache::{CacheKey, WaveformCache};
+use crate::waveform_jobs::{WaveformJobs, WaveformUpdate};
+
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 pub struct MusicPlayerWindow {
@@ -31,30 +41,62 @@ pub struct MusicPlayerWindow {
     bg_palette: Rc<RefCell<[(f64, f64, f64); 4]>>,
     
     // Tabs
+    tabs_box: GtkBox,
     player_tab: Button,
+    lyrics_tab: Button,
     library_tab: Button,
     stack: Stack,
     
     // Player view widgets
     album_art: Image,
     cava_area: DrawingArea,
-    cava_bars: Arc<Mutex<Vec<u8>>>,
+    cava_bars: Arc<Mutex<Vec<u16>>>,
+    visualizer_slots: VisualizerSlots,
+    visualizer_layout: Rc<Cell<VisualizerLayout>>,
+    // Kept alive for the lifetime of the window; paused while idle
+    visualizer: Option<Rc<VisualizerView>>,
+    visualizer_activity: Rc<Cell<VisualizerActivity>>,
     song_title: Label,
     song_artist: Label,
     song_album: Label,
+    song_details: Label,
     time_label: Label,
     total_time_label: Label,
     waveform_area: DrawingArea,
+    // Moves the playhead between polls while playing
+    waveform_ticker: FrameTicker,
     waveform_peaks: Rc<RefCell<Vec<PeakPair>>>,
+    // Dense levels the drawn bars are re-binned from when the width changes
+    waveform_pyramid: Rc<RefCell<Option<PeakPyramid>>>,
     waveform_position: Rc<RefCell<f64>>,
+    // Pointer x over the waveform, for the seek preview line
+    waveform_hover: Rc<RefCell<Option<f64>>>,
+    /// Silences and section boundaries, once the whole track is decoded
+    waveform_sections: Rc<RefCell<Option<TrackSections>>>,
+    waveform_spectrogram: Rc<RefCell<Option<SpectrogramImage>>>,
+    /// Tempo and key of the current song, once known
+    track_analysis: Rc<RefCell<Option<TrackAnalysis>>>,
+    seek_bar_mode: Rc<RefCell<SeekBarMode>>,
+    waveform_style: WaveformStyle,
+    waveform_cache: WaveformCache,
+    track_info: gtk::Popover,
     lyrics_scroll: ScrolledWindow,
     lyrics_box: GtkBox,
+    // Word-by-word highlight of the current line
+    lyrics_ticker: FrameTicker,
+    lyrics_status: Label,
+    lyrics_resume_btn: Button,
     play_btn: Button,
     prev_btn: Button,
     next_btn: Button,
     volume_scale: Scale,
     volume_percent: Label,
     queue_btn: Button,
+    theme_toggle: Button,
+
+    // Full-page karaoke lyrics
+    karaoke_area: DrawingArea,
+    karaoke_ticker: FrameTicker,
     
     // Library view
     library_view: TreeView,
@@ -72,6 +114,15 @@ pub struct MusicPlayerWindow {
     current_song_file: Rc<RefCell<String>>,
     current_lyrics: Rc<RefCell<Option<LRCParser>>>,
     current_lyrics_index: Rc<RefCell<Option<usize>>>,
+    lyrics_follow: Rc<RefCell<LyricsFollow>>,
+    // (artist, title) of the current song, used to locate lyrics files
+    current_song_info: Rc<RefCell<(String, String)>>,
+    // Active tap-to-sync session, if the lyrics editor is open
+    lyrics_sync: Rc<RefCell<Option<LyricsSync>>>,
+    lyrics_display: Rc<RefCell<LyricsDisplay>>,
+    playback_clock: Rc<RefCell<PlaybackClock>>,
+    config: Rc<Config>,
+    karaoke_fullscreen: Rc<RefCell<bool>>,
     is_seeking: Rc<RefCell<bool>>,
     shuffle_enabled: Rc<RefCell<bool>>,
     repeat_enabled: Rc<RefCell<bool>>,
@@ -79,6 +130,300 @@ pub struct MusicPlayerWindow {
     art_cache: Rc<RefCell<HashMap<String, Option<String>>>>,
 }
 
+/// MPD's elapsed time as of the last status poll, so animations can
+/// interpolate between the 500 ms updates.
+#[derive(Clone, Copy)]
+struct PlaybackClock {
+    elapsed: f64,
+    sampled_at: Instant,
+    playing: bool,
+    duration: f64,
+}
+
+impl PlaybackClock {
+    fn now(&self) -> f64 {
+        if self.playing {
+            self.elapsed + self.sampled_at.elapsed().as_secs_f64()
+        } else {
+            self.elapsed
+        }
+    }
+
+    /// Interpolated position as a 0.0–1.0 fraction, if the song length is known.
+    fn fraction(&self) -> Option<f64> {
+        (self.duration > 0.0).then(|| (self.now() / self.duration).clamp(0.0, 1.0))
+    }
+}
+
+/// What the visualizer runs on: it's paused unless MPD is playing and the
+/// bars are on screen.
+#[derive(Clone, Copy, Default)]
+struct VisualizerActivity {
+    playing: bool,
+    mapped: bool,
+    iconified: bool,
+}
+
+impl VisualizerActivity {
+    fn active(&self) -> bool {
+        self.playing && self.mapped && !self.iconified
+    }
+}
+
+/// How often to look for new frames while playing but the bars are still.
+const VISUALIZER_WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A running visualizer and the animation of its bars. While they move, the
+/// area's frame clock drives a `BarRenderer` and redraws; once they've
+/// settled, a slow check for new frames takes over (the backends run on
+/// their own threads and can't wake the main loop), and while the visualizer
+/// is paused nothing runs at all.
+struct VisualizerView {
+    visualizer: Visualizer,
+    bars: Arc<Mutex<Vec<u16>>>,
+    area: DrawingArea,
+    renderer: RefCell<BarRenderer>,
+    active: Cell<bool>,
+    ticking: Cell<bool>,
+    watch: RefCell<Option<glib::SourceId>>,
+}
+
+impl VisualizerView {
+    fn new(visualizer: Visualizer, area: DrawingArea) -> Rc<Self> {
+        let bars = visualizer.get_bars_arc();
+        let num_bars = bars.lock().unwrap_or_else(|e| e.into_inner()).len();
+        Rc::new(Self {
+            visualizer,
+            bars,
+            area,
+            renderer: RefCell::new(BarRenderer::new(num_bars)),
+            active: Cell::new(false),
+            ticking: Cell::new(false),
+            watch: RefCell::new(None),
+        })
+    }
+
+    /// Resume or pause the backend; either way the bars animate to where
+    /// it leaves them.
+    fn set_active(self: &Rc<Self>, active: bool) {
+        if self.active.replace(active) == active {
+            return;
+        }
+        self.visualizer.set_paused(!active);
+        self.animate();
+    }
+
+    /// Start driving the bars from the frame clock, if not already.
+    fn animate(self: &Rc<Self>) {
+        if let Some(watch) = self.watch.take() {
+            watch.remove();
+        }
+        if self.ticking.replace(true) {
+            return;
+        }
+        let view = self.clone();
+        self.area.add_tick_callback(move |area, clock| {
+            let now = clock.frame_time() as f64 / 1_000_000.0;
+            let mut renderer = view.renderer.borrow_mut();
+            renderer.receive(&view.bars.lock().unwrap_or_else(|e| e.into_inner()), now);
+            if renderer.advance(now) {
+                area.queue_draw();
+            }
+            if !renderer.settled(now) {
+                return glib::ControlFlow::Continue;
+            }
+            drop(renderer);
+            view.ticking.set(false);
+            if view.active.get() {
+                view.watch_for_frames();
+            }
+            glib::ControlFlow::Break
+        });
+    }
+
+    fn watch_for_frames(self: &Rc<Self>) {
+        let view = self.clone();
+        let watch = glib::timeout_add_local(VISUALIZER_WATCH_INTERVAL, move || {
+            let fresh = view.renderer.borrow().is_new(&view.bars.lock().unwrap_or_else(|e| e.into_inner()));
+            if !fresh {
+                return glib::ControlFlow::Continue;
+            }
+            // Returning Break removes this source, so animate mustn't
+            view.watch.take();
+            view.animate();
+            glib::ControlFlow::Break
+        });
+        *self.watch.borrow_mut() = Some(watch);
+    }
+}
+
+/// Room the visualizer gets: beside the art, under it (after a gap), and
+/// around it (outside a gap, following its corner radius from style.css).
+const VISUALIZER_SIDE_DEPTH: f64 = 48.0;
+const VISUALIZER_UNDER_GAP: f64 = 8.0;
+const VISUALIZER_UNDER_DEPTH: f64 = 40.0;
+const VISUALIZER_RING_GAP: f64 = 6.0;
+const VISUALIZER_RING_DEPTH: f64 = 30.0;
+const ALBUM_ART_RADIUS: f64 = 20.0;
+
+/// The widgets around the album art that make room for each visualizer
+/// layout; right-click the art (or press `v`) to switch layouts.
+#[derive(Clone)]
+struct VisualizerSlots {
+    art_events: gtk::EventBox,
+    art_frame: GtkBox,
+    art_row: GtkBox,
+    side_spacers: [DrawingArea; 2],
+    under_spacer: DrawingArea,
+}
+
+/// One frame of a `FrameTicker`'s animation; false once nothing moves.
+type FrameStep = Rc<dyn Fn(&gtk::Widget) -> bool>;
+
+/// Drives a widget's animation from its frame clock only while there's
+/// something to move. `start` (called on map, while playing, or when what
+/// the widget shows changes) runs the step each frame until it returns false.
+#[derive(Clone)]
+struct FrameTicker {
+    widget: gtk::Widget,
+    step: Rc<RefCell<FrameStep>>,
+    ticking: Rc<Cell<bool>>,
+}
+
+impl FrameTicker {
+    fn new(widget: &impl IsA<gtk::Widget>) -> Self {
+        let ticker = Self {
+            widget: widget.clone().upcast(),
+            step: Rc::new(RefCell::new(Rc::new(|_: &gtk::Widget| false) as FrameStep)),
+            ticking: Rc::new(Cell::new(false)),
+        };
+        let on_map = ticker.clone();
+        widget.connect_map(move |_| on_map.start());
+        ticker
+    }
+
+    fn on_frame(&self, step: impl Fn(&gtk::Widget) -> bool + 'static) {
+        *self.step.borrow_mut() = Rc::new(step);
+    }
+
+    /// Tick until the step settles; a no-op while hidden or already ticking.
+    fn start(&self) {
+        if !self.widget.is_mapped() || self.ticking.replace(true) {
+            return;
+        }
+        let step = self.step.clone();
+        let ticking = self.ticking.clone();
+        self.widget.add_tick_callback(move |widget, _| {
+            let step = step.borrow().clone();
+            if widget.is_mapped() && step(widget) {
+                return glib::ControlFlow::Continue;
+            }
+            ticking.set(false);
+            glib::ControlFlow::Break
+        });
+    }
+}
+
+/// Instrumental gaps at least this long (seconds) get a countdown in the karaoke view.
+const KARAOKE_COUNTDOWN_MIN_GAP: f64 = 4.0;
+
+/// Seconds moved per scroll step or arrow press on the waveform (Shift: five times as far).
+const FINE_SEEK_STEP: f64 = 1.0;
+
+/// How long auto-follow stays paused after the user scrolls the lyrics by hand.
+const LYRICS_FOLLOW_GRACE: Duration = Duration::from_secs(5);
+
+/// Auto-follow state for the lyrics pane.
+#[derive(Default)]
+struct LyricsFollow {
+    /// Where the running scroll animation is heading
+    target: Option<f64>,
+    /// The running animation's timer, so new targets reuse it instead of racing it
+    anim: Option<glib::SourceId>,
+    /// Set by a manual scroll; auto-follow resumes at this instant
+    paused_until: Option<Instant>,
+}
+
+impl LyricsFollow {
+    fn cancel_animation(&mut self) {
+        self.target = None;
+        if let Some(id) = self.anim.take() {
+            id.remove();
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused_until.is_some_and(|t| Instant::now() < t)
+    }
+}
+
+/// Which lyrics track(s) to show when a translation is available.
+#[derive(Clone, Copy, PartialEq)]
+enum LyricsDisplay {
+    Original,
+    Translation,
+    Both,
+}
+
+impl LyricsDisplay {
+    fn next(self) -> Self {
+        match self {
+            LyricsDisplay::Both => LyricsDisplay::Original,
+            LyricsDisplay::Original => LyricsDisplay::Translation,
+            LyricsDisplay::Translation => LyricsDisplay::Both,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            LyricsDisplay::Original => "Lyrics: original only",
+            LyricsDisplay::Translation => "Lyrics: translation only",
+            LyricsDisplay::Both => "Lyrics: original + translation",
+        }
+    }
+}
+
+/// What the seek bar shows; right-click it or press `s` to switch.
+#[derive(Clone, Copy, PartialEq)]
+enum SeekBarMode {
+    Waveform,
+    Spectrogram,
+}
+
+impl SeekBarMode {
+    /// From `[waveform] view` (`waveform` or `spectrogram`).
+    fn from_config(config: &Config) -> Self {
+        match config.get("waveform", "view") {
+            Some(view) if view.eq_ignore_ascii_case("spectrogram") => SeekBarMode::Spectrogram,
+            _ => SeekBarMode::Waveform,
+        }
+    }
+
+    fn toggle(self) -> Self {
+        match self {
+            SeekBarMode::Waveform => SeekBarMode::Spectrogram,
+            SeekBarMode::Spectrogram => SeekBarMode::Waveform,
+        }
+    }
+}
+
+/// Art, palette and lyrics computed ahead of time for the next queued song.
+struct Prefetched {
+    file: String,
+    pixbuf: Option<Pixbuf>,
+    palette: Option<[(f64, f64, f64); 4]>,
+    lyrics: Option<LRCParser>,
+}
+
+/// Progress of the loudness panel's background scan.
+enum LoudnessUpdate {
+    Track(Option<LoudnessSummary>),
+    /// Files done so far, of the total in the album
+    Progress(usize, usize),
+    /// Every album file that could be measured, and the number of files
+    Album(Vec<(PathBuf, TrackLoudness)>, usize),
+}
+
 impl MusicPlayerWindow {
     pub fn new(app: &Application) -> Self {
         let window = ApplicationWindow::builder()
@@ -103,8 +448,20 @@ impl MusicPlayerWindow {
         let current_song_file = Rc::new(RefCell::new(String::new()));
         let current_lyrics = Rc::new(RefCell::new(None));
         let current_lyrics_index = Rc::new(RefCell::new(None));
+        let lyrics_follow = Rc::new(RefCell::new(LyricsFollow::default()));
+        let current_song_info = Rc::new(RefCell::new((String::new(), String::new())));
+        let lyrics_sync: Rc<RefCell<Option<LyricsSync>>> = Rc::new(RefCell::new(None));
+        let lyrics_display = Rc::new(RefCell::new(LyricsDisplay::Both));
+        let playback_clock = Rc::new(RefCell::new(PlaybackClock {
+            elapsed: 0.0,
+            sampled_at: Instant::now(),
+            playing: false,
+            duration: 0.0,
+        }));
         let waveform_peaks: Rc<RefCell<Vec<PeakPair>>> = Rc::new(RefCell::new(Vec::new()));
         let waveform_position: Rc<RefCell<f64>> = Rc::new(RefCell::new(0.0));
+        let config = Rc::new(Config::load());
+        let waveform_style = WaveformStyle::from_config(&config);
         let is_seeking = Rc::new(RefCell::new(false));
         let shuffle_enabled = Rc::new(RefCell::new(false));
         let repeat_enabled = Rc::new(RefCell::new(false));
@@ -148,24 +505,7 @@ impl MusicPlayerWindow {
             if needs_render {
                 if let Ok(surf) = cairo::ImageSurface::create(cairo::Format::ARgb32, w, h) {
                     let cr2 = cairo::Context::new(&surf).unwrap();
-                    let (tl_r, tl_g, tl_b) = pal[0];
-                    let (tr_r, tr_g, tr_b) = pal[1];
-                    let (bl_r, bl_g, bl_b) = pal[2];
-                    let (br_r, br_g, br_b) = pal[3];
-
-                    let mesh = cairo::Mesh::new();
-                    mesh.begin_patch();
-                    mesh.move_to(0.0, 0.0);
-                    mesh.line_to(w as f64, 0.0);
-                    mesh.line_to(w as f64, h as f64);
-                    mesh.line_to(0.0, h as f64);
-                    mesh.set_corner_color_rgb(cairo::MeshCorner::MeshCorner0, tl_r, tl_g, tl_b);
-                    mesh.set_corner_color_rgb(cairo::MeshCorner::MeshCorner1, tr_r, tr_g, tr_b);
-                    mesh.set_corner_color_rgb(cairo::MeshCorner::MeshCorner2, br_r, br_g, br_b);
-                    mesh.set_corner_color_rgb(cairo::MeshCorner::MeshCorner3, bl_r, bl_g, bl_b);
-                    mesh.end_patch();
-                    cr2.set_source(&mesh).unwrap();
-                    cr2.paint().unwrap();
+                    paint_palette_gradient(&cr2, w as f64, h as f64, &pal);
 
                     // Bake noise dither into the cached surface too
                     {
@@ -240,11 +580,16 @@ impl MusicPlayerWindow {
         player_tab.style_context().add_class("tab-button");
         player_tab.style_context().add_class("active");
 
+        let lyrics_tab = Button::with_label("Lyrics");
+        lyrics_tab.set_widget_name("tab-button");
+        lyrics_tab.style_context().add_class("tab-button");
+
         let library_tab = Button::with_label("Library");
         library_tab.set_widget_name("tab-button");
         library_tab.style_context().add_class("tab-button");
 
         tabs_box.pack_start(&player_tab, false, false, 0);
+        tabs_box.pack_start(&lyrics_tab, false, false, 0);
         tabs_box.pack_start(&library_tab, false, false, 0);
         main_box.pack_start(&tabs_box, false, false, 0);
 
@@ -257,6 +602,10 @@ impl MusicPlayerWindow {
         let (player_view, player_widgets) = Self::create_player_view();
         stack.add_named(&player_view, "player");
 
+        // Karaoke lyrics page — drawn entirely in connect_karaoke_view
+        let karaoke_area = DrawingArea::new();
+        stack.add_named(&karaoke_area, "lyrics");
+
         // Create library view
         let (library_view_widget, library_view, library_store) = Self::create_library_view();
         stack.add_named(&library_view_widget, "library");
@@ -375,34 +724,64 @@ impl MusicPlayerWindow {
         // Apply CSS
         Self::load_css();
 
-        // Start CAVA visualizer (24 bars to fit album art height)
-        let cava_num_bars: usize = 24;
-        let cava_bars: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(vec![0u8; cava_num_bars]));
-        // Keep the CAVA process alive for the lifetime of the window
-        let _cava_process: Rc<RefCell<Option<CavaVisualizer>>> = Rc::new(RefCell::new(None));
-        if let Some(cava) = CavaVisualizer::new(cava_num_bars) {
-            let cava_bars_for_draw = cava.get_bars_arc();
-            *_cava_process.borrow_mut() = Some(cava);
-            // Set up CAVA draw callback with palette colors
+        // Start the visualizer: the native analyser on MPD's FIFO output if
+        // there is one, otherwise CAVA
+        let visualizer_settings = VisualizerSettings::from_config(&config);
+        let visualizer_slots = player_widgets.18.clone();
+        let visualizer_layout = Rc::new(Cell::new(visualizer_settings.layout));
+        let cava_bars: Arc<Mutex<Vec<u16>>> = Arc::new(Mutex::new(vec![0; visualizer_settings.bars]));
+        let visualizer_activity = Rc::new(Cell::new(VisualizerActivity::default()));
+        let visualizer = Visualizer::start(&config, visualizer_settings)
+            .map(|started| VisualizerView::new(started, player_widgets.1.clone()));
+        if let Some(ref view) = visualizer {
+            Self::apply_visualizer_layout(&visualizer_slots, visualizer_settings.layout);
+            // Set up CAVA draw callback with palette colors, placed around the art
             let bg_palette_for_cava = bg_palette.clone();
+            let art_frame = visualizer_slots.art_frame.clone();
+            let layout_for_draw = visualizer_layout.clone();
+            let view_for_draw = view.clone();
             player_widgets.1.connect_draw(move |widget, cr| {
-                let w = widget.allocated_width() as f64;
-                let h = widget.allocated_height() as f64;
-                let bars = cava_bars_for_draw.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                let Some((x, y)) = art_frame.translate_coordinates(widget, 0, 0) else {
+                    return glib::Propagation::Stop;
+                };
+                let art = (x as f64, y as f64, art_frame.allocated_width() as f64, art_frame.allocated_height() as f64);
                 let pal = *bg_palette_for_cava.borrow();
-                Self::draw_cava_bars(cr, &bars, w, h, &pal);
+                Self::draw_cava_bars(cr, view_for_draw.renderer.borrow().heights(), layout_for_draw.get(), art, &pal);
                 glib::Propagation::Stop
             });
-            // Redraw CAVA at ~30fps
-            let cava_area_for_timer = player_widgets.1.clone();
-            let _keep_cava_alive = _cava_process.clone();
-            glib::timeout_add_local(std::time::Duration::from_millis(33), move || {
-                let _alive = &_keep_cava_alive; // prevent drop
-                cava_area_for_timer.queue_draw();
-                glib::ControlFlow::Continue
+            // Right-click the album art for the next layout
+            let slots_for_click = visualizer_slots.clone();
+            let layout_for_click = visualizer_layout.clone();
+            let cava_area_for_click = player_widgets.1.clone();
+            visualizer_slots.art_events.connect_button_press_event(move |_, event| {
+                if event.button() != 3 {
+                    return glib::Propagation::Proceed;
+                }
+                Self::cycle_visualizer_layout(&slots_for_click, &layout_for_click, &cava_area_for_click);
+                glib::Propagation::Stop
+            });
+
+            // Pause the analysis while the bars are off screen (another tab,
+            // or the window hidden or minimised)
+            let visualizer_for_map = visualizer.clone();
+            let activity_for_map = visualizer_activity.clone();
+            player_widgets.1.connect_map(move |_| {
+                Self::update_visualizer_activity(&visualizer_for_map, &activity_for_map, |a| a.mapped = true);
+            });
+            let visualizer_for_unmap = visualizer.clone();
+            let activity_for_unmap = visualizer_activity.clone();
+            player_widgets.1.connect_unmap(move |_| {
+                Self::update_visualizer_activity(&visualizer_for_unmap, &activity_for_unmap, |a| a.mapped = false);
+            });
+            let visualizer_for_state = visualizer.clone();
+            let activity_for_state = visualizer_activity.clone();
+            window.connect_window_state_event(move |_, event| {
+                let iconified = event.new_window_state().contains(gdk::WindowState::ICONIFIED);
+                Self::update_visualizer_activity(&visualizer_for_state, &activity_for_state, |a| a.iconified = iconified);
+                glib::Propagation::Proceed
             });
         } else {
-            // CAVA not available — hide the drawing area
+            // Neither the FIFO nor CAVA is available — hide the drawing area
             player_widgets.1.set_no_show_all(true);
             player_widgets.1.hide();
         }
@@ -412,28 +791,51 @@ impl MusicPlayerWindow {
             mpd,
             background,
             bg_palette,
+            tabs_box,
             player_tab,
+            lyrics_tab,
             library_tab,
             stack,
             album_art: player_widgets.0,
             cava_area: player_widgets.1,
             cava_bars,
-            song_title: player_widgets.2,
+            visualizer_slots,
+            visualizer_layout,
+            visualizer,
+            visualizer_activity,
+            song_title: player_widgets.2.clone(),
             song_artist: player_widgets.3,
             song_album: player_widgets.4,
+            song_details: player_widgets.17,
             time_label: player_widgets.5,
             total_time_label: player_widgets.6,
+            waveform_ticker: FrameTicker::new(&player_widgets.7),
             waveform_area: player_widgets.7,
             waveform_peaks,
+            waveform_pyramid: Rc::new(RefCell::new(None)),
             waveform_position,
+            waveform_hover: Rc::new(RefCell::new(None)),
+            waveform_sections: Rc::new(RefCell::new(None)),
+            waveform_spectrogram: Rc::new(RefCell::new(None)),
+            track_analysis: Rc::new(RefCell::new(None)),
+            seek_bar_mode: Rc::new(RefCell::new(SeekBarMode::from_config(&config))),
+            waveform_style,
+            waveform_cache: WaveformCache::new(Self::cache_dir().join("waveforms")),
+            track_info: gtk::Popover::new(Some(&player_widgets.2)),
             lyrics_scroll: player_widgets.8,
+            lyrics_ticker: FrameTicker::new(&player_widgets.9),
             lyrics_box: player_widgets.9,
+            lyrics_status: player_widgets.15,
+            lyrics_resume_btn: player_widgets.16,
             play_btn: player_widgets.10,
             prev_btn: player_widgets.11,
             next_btn: player_widgets.12,
             volume_scale: player_widgets.13,
             volume_percent: player_widgets.14,
             queue_btn,
+            theme_toggle,
+            karaoke_ticker: FrameTicker::new(&karaoke_area),
+            karaoke_area,
             library_view,
             library_store,
             queue_revealer,
@@ -445,6 +847,13 @@ impl MusicPlayerWindow {
             current_song_file,
             current_lyrics,
             current_lyrics_index,
+            lyrics_follow,
+            current_song_info,
+            lyrics_sync,
+            lyrics_display,
+            playback_clock,
+            config,
+            karaoke_fullscreen: Rc::new(RefCell::new(false)),
             is_seeking,
             shuffle_enabled,
             repeat_enabled,
@@ -452,82 +861,250 @@ impl MusicPlayerWindow {
         };
 
         player.connect_signals();
+        player.connect_shortcuts();
+        player.connect_karaoke_view();
+        player.connect_lyrics_follow();
+        player.connect_track_info();
         player.load_library_from_music();
         player.load_queue_from_mpd();
         player.precache_all_album_art();
         player.start_update_loop();
+        player.connect_karaoke_highlight();
 
         player
     }
 
-    /// Draw horizontal CAVA bars — each bar extends right-to-left based on amplitude.
-    /// Bars are stacked vertically and colored using a vertical gradient from the album palette.
+    /// Apply `change` to the visualizer's activity, pausing or resuming it.
+    fn update_visualizer_activity(
+        visualizer: &Option<Rc<VisualizerView>>,
+        activity: &Cell<VisualizerActivity>,
+        change: impl FnOnce(&mut VisualizerActivity),
+    ) {
+        let mut updated = activity.get();
+        change(&mut updated);
+        activity.set(updated);
+        if let Some(ref view) = visualizer {
+            view.set_active(updated.active());
+        }
+    }
+
+    /// Make room for `layout` around the album art: beside it, under it, or
+    /// all around it.
+    fn apply_visualizer_layout(slots: &VisualizerSlots, layout: VisualizerLayout) {
+        let sides = layout != VisualizerLayout::Horizontal;
+        for spacer in &slots.side_spacers {
+            spacer.set_visible(sides);
+        }
+        slots.under_spacer.set_visible(layout == VisualizerLayout::Horizontal);
+        let ring = if layout == VisualizerLayout::Radial { (VISUALIZER_RING_GAP + VISUALIZER_RING_DEPTH) as i32 } else { 0 };
+        slots.art_row.set_margin_top(ring);
+        slots.art_row.set_margin_bottom(ring);
+    }
+
+    /// Switch the visualizer to its next layout.
+    fn cycle_visualizer_layout(slots: &VisualizerSlots, layout: &Cell<VisualizerLayout>, area: &DrawingArea) {
+        let next = layout.get().next();
+        layout.set(next);
+        Self::apply_visualizer_layout(slots, next);
+        area.queue_draw();
+    }
+
+    /// Draw the CAVA bars (heights 0.0–1.0) around the album art, `art`
+    /// being its (x, y, w, h) in the drawing area. Vertical: stacked left of the art, growing
+    /// right-to-left. Horizontal: in a row under it, growing upwards. Radial:
+    /// around its rounded edge, growing outwards, over a halo that swells
+    /// with the overall level. Bars take a gradient from the album palette,
+    /// brightened, with alpha rising with amplitude.
     /// `palette` is [top-left, top-right, bottom-left, bottom-right] RGB tuples.
-    fn draw_cava_bars(cr: &cairo::Context, bars: &[u8], w: f64, h: f64, palette: &[(f64, f64, f64); 4]) {
+    fn draw_cava_bars(
+        cr: &cairo::Context,
+        bars: &[f64],
+        layout: VisualizerLayout,
+        art: (f64, f64, f64, f64),
+        palette: &[(f64, f64, f64); 4],
+    ) {
         let num_bars = bars.len();
         if num_bars == 0 { return; }
+        let (art_x, art_y, art_w, art_h) = art;
+        let gap = 2.0;
+        // Where along the gradient each bar is (0.0–1.0)
+        let position = |i: usize| if num_bars > 1 { i as f64 / (num_bars - 1) as f64 } else { 0.5 };
+
+        match layout {
+            VisualizerLayout::Vertical => {
+                // Vertical gradient: left-side colors (top-left -> bottom-left)
+                let (bar_height, step) = Self::bar_extent(art_h, num_bars, gap);
+                for (i, &fraction) in bars.iter().enumerate() {
+                    let bar_width = (fraction * VISUALIZER_SIDE_DEPTH).max(2.0);
+                    Self::set_bar_color(cr, Self::mix_rgb(palette[0], palette[2], position(i)), fraction);
+                    // Bars grow right-to-left (base against album art on the right)
+                    let y = art_y + i as f64 * step;
+                    Self::rounded_bar(cr, art_x - bar_width, y, bar_width, bar_height, bar_height * 0.3);
+                }
+            }
+            VisualizerLayout::Horizontal => {
+                // Horizontal gradient: bottom colors (bottom-left -> bottom-right)
+                let (bar_width, step) = Self::bar_extent(art_w, num_bars, gap);
+                let base = art_y + art_h + VISUALIZER_UNDER_GAP + VISUALIZER_UNDER_DEPTH;
+                for (i, &fraction) in bars.iter().enumerate() {
+                    let bar_height = (fraction * VISUALIZER_UNDER_DEPTH).max(2.0);
+                    Self::set_bar_color(cr, Self::mix_rgb(palette[2], palette[3], position(i)), fraction);
+                    let x = art_x + i as f64 * step;
+                    Self::rounded_bar(cr, x, base - bar_height, bar_width, bar_height, bar_width * 0.3);
+                }
+            }
+            VisualizerLayout::Radial => {
+                // Around a rounded rectangle just outside the art's own
+                // corners, from the bottom centre clockwise; each bar takes
+                // the palette color of the corner it's nearest
+                let ring = (
+                    art_x - VISUALIZER_RING_GAP,
+                    art_y - VISUALIZER_RING_GAP,
+                    art_w + 2.0 * VISUALIZER_RING_GAP,
+                    art_h + 2.0 * VISUALIZER_RING_GAP,
+                );
+                let radius = ALBUM_ART_RADIUS + VISUALIZER_RING_GAP;
+                let length = Self::ring_length(ring, radius);
+
+                // Halo in the palette's average color, outside the ring's
+                // inner edge and as wide as the average bar is long
+                let level = bars.iter().sum::<f64>() / num_bars as f64;
+                if level > 0.0 {
+                    let (r, g, b) = Self::mix_rgb(Self::mix_rgb(palette[0], palette[1], 0.5), Self::mix_rgb(palette[2], palette[3], 0.5), 0.5);
+                    cr.set_source_rgba((r * 1.6).min(1.0), (g * 1.6).min(1.0), (b * 1.6).min(1.0), 0.25 * level);
+                    let width = level * VISUALIZER_RING_DEPTH;
+                    let half = width / 2.0;
+                    cr.set_line_width(width);
+                    Self::rounded_rect_path(cr, ring.0 - half, ring.1 - half, ring.2 + width, ring.3 + width, radius + half);
+                    let _ = cr.stroke();
+                }
+
+                let thickness = (length / num_bars as f64 * 0.55).clamp(1.0, 8.0);
+                for (i, &fraction) in bars.iter().enumerate() {
+                    let (x, y, angle) = Self::ring_point(ring, radius, (i as f64 + 0.5) / num_bars as f64 * length);
+                    let (u, v) = ((x - ring.0) / ring.2, (y - ring.1) / ring.3);
+                    let top = Self::mix_rgb(palette[0], palette[1], u);
+                    let bottom = Self::mix_rgb(palette[2], palette[3], u);
+                    Self::set_bar_color(cr, Self::mix_rgb(top, bottom, v), fraction);
+
+                    let bar_length = (fraction * VISUALIZER_RING_DEPTH).max(thickness);
+                    let _ = cr.save();
+                    cr.translate(x, y);
+                    cr.rotate(angle);
+                    Self::rounded_bar(cr, 0.0, -thickness / 2.0, bar_length, thickness, thickness / 2.0);
+                    let _ = cr.restore();
+                }
+            }
+        }
+    }
 
-        // Vertical gradient: interpolate left-side colors (top-left -> bottom-left)
-        let (tl_r, tl_g, tl_b) = palette[0];
-        let (bl_r, bl_g, bl_b) = palette[2];
+    /// Size of each of `count` bars along `span`, and the step from one bar to
+    /// the next. The gap shrinks below `gap` when needed so bars stay at least
+    /// 1 px wide while the span has room for that.
+    fn bar_extent(span: f64, count: usize, gap: f64) -> (f64, f64) {
+        let gaps = count.saturating_sub(1) as f64;
+        let gap = if gaps > 0.0 { ((span - count as f64) / gaps).clamp(0.0, gap) } else { 0.0 };
+        let size = (span - gap * gaps) / count as f64;
+        (size, size + gap)
+    }
 
-        let gap = 2.0;
-        let bar_height = (h - gap * (num_bars as f64 - 1.0)) / num_bars as f64;
-        let corner_radius = bar_height * 0.3;
-
-        for (i, &val) in bars.iter().enumerate() {
-            let y = i as f64 * (bar_height + gap);
-            let fraction = val as f64 / 255.0;
-            let bar_width = (fraction * w).max(2.0);
-
-            // Vertical interpolation factor for this bar
-            let t = if num_bars > 1 { i as f64 / (num_bars - 1) as f64 } else { 0.5 };
-            let r = tl_r + (bl_r - tl_r) * t;
-            let g = tl_g + (bl_g - tl_g) * t;
-            let b = tl_b + (bl_b - tl_b) * t;
-
-            // Brighten the palette color and modulate alpha by amplitude
-            let brighten = 1.6;
-            let br = (r * brighten).min(1.0);
-            let bg = (g * brighten).min(1.0);
-            let bb = (b * brighten).min(1.0);
-            let alpha = 0.4 + fraction * 0.6;
-            cr.set_source_rgba(br, bg, bb, alpha);
-
-            // Bars grow right-to-left (base against album art on the right)
-            let x = w - bar_width;
-            if bar_width > corner_radius * 2.0 {
-                cr.new_path();
-                cr.arc(x + corner_radius, y + corner_radius, corner_radius, std::f64::consts::PI, 1.5 * std::f64::consts::PI);
-                cr.arc(x + bar_width - corner_radius, y + corner_radius, corner_radius, 1.5 * std::f64::consts::PI, 0.0);
-                cr.arc(x + bar_width - corner_radius, y + bar_height - corner_radius, corner_radius, 0.0, 0.5 * std::f64::consts::PI);
-                cr.arc(x + corner_radius, y + bar_height - corner_radius, corner_radius, 0.5 * std::f64::consts::PI, std::f64::consts::PI);
-                cr.close_path();
-                cr.fill().unwrap();
-            } else {
-                cr.rectangle(x, y, bar_width, bar_height);
-                cr.fill().unwrap();
+    fn mix_rgb(from: (f64, f64, f64), to: (f64, f64, f64), t: f64) -> (f64, f64, f64) {
+        (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t, from.2 + (to.2 - from.2) * t)
+    }
+
+    /// Brighten the palette color and modulate alpha by amplitude.
+    fn set_bar_color(cr: &cairo::Context, (r, g, b): (f64, f64, f64), fraction: f64) {
+        let brighten = 1.6;
+        let alpha = 0.4 + fraction * 0.6;
+        cr.set_source_rgba((r * brighten).min(1.0), (g * brighten).min(1.0), (b * brighten).min(1.0), alpha);
+    }
+
+    /// Fill a bar with rounded corners, or a plain rectangle if it's too
+    /// small to round.
+    fn rounded_bar(cr: &cairo::Context, x: f64, y: f64, w: f64, h: f64, radius: f64) {
+        if w >= radius * 2.0 && h >= radius * 2.0 {
+            Self::rounded_rect_path(cr, x, y, w, h, radius);
+        } else {
+            cr.rectangle(x, y, w, h);
+        }
+        let _ = cr.fill();
+    }
+
+    fn rounded_rect_path(cr: &cairo::Context, x: f64, y: f64, w: f64, h: f64, radius: f64) {
+        use std::f64::consts::PI;
+        cr.new_path();
+        cr.arc(x + radius, y + radius, radius, PI, 1.5 * PI);
+        cr.arc(x + w - radius, y + radius, radius, 1.5 * PI, 0.0);
+        cr.arc(x + w - radius, y + h - radius, radius, 0.0, 0.5 * PI);
+        cr.arc(x + radius, y + h - radius, radius, 0.5 * PI, PI);
+        cr.close_path();
+    }
+
+    /// Perimeter of the rounded rectangle `rect` (x, y, w, h).
+    fn ring_length(rect: (f64, f64, f64, f64), radius: f64) -> f64 {
+        let (_, _, w, h) = rect;
+        2.0 * (w - 2.0 * radius).max(0.0) + 2.0 * (h - 2.0 * radius).max(0.0) + 2.0 * std::f64::consts::PI * radius
+    }
+
+    /// The point `distance` along the edge of the rounded rectangle `rect`,
+    /// from the bottom centre clockwise, and the angle pointing outwards there.
+    fn ring_point(rect: (f64, f64, f64, f64), radius: f64, distance: f64) -> (f64, f64, f64) {
+        use std::f64::consts::{FRAC_PI_2, PI};
+        let (x, y, w, h) = rect;
+        let (cx, cy) = (x + w / 2.0, y + h / 2.0);
+        let (a, b) = ((w / 2.0 - radius).max(0.0), (h / 2.0 - radius).max(0.0));
+        let corner = FRAC_PI_2 * radius;
+
+        // (length, start, direction or corner centre, outward angle at start)
+        enum Piece {
+            Side((f64, f64), (f64, f64), f64),
+            Corner((f64, f64), f64),
+        }
+        let pieces = [
+            (a, Piece::Side((cx, y + h), (-1.0, 0.0), FRAC_PI_2)),
+            (corner, Piece::Corner((cx - a, cy + b), FRAC_PI_2)),
+            (2.0 * b, Piece::Side((x, cy + b), (0.0, -1.0), PI)),
+            (corner, Piece::Corner((cx - a, cy - b), PI)),
+            (2.0 * a, Piece::Side((cx - a, y), (1.0, 0.0), 1.5 * PI)),
+            (corner, Piece::Corner((cx + a, cy - b), 1.5 * PI)),
+            (2.0 * b, Piece::Side((x + w, cy - b), (0.0, 1.0), 0.0)),
+            (corner, Piece::Corner((cx + a, cy + b), 0.0)),
+            (a, Piece::Side((cx + a, y + h), (-1.0, 0.0), FRAC_PI_2)),
+        ];
+        let mut left = distance.rem_euclid(Self::ring_length(rect, radius));
+        for (i, (length, piece)) in pieces.iter().enumerate() {
+            if left > *length && i + 1 < pieces.len() {
+                left -= length;
+                continue;
             }
+            return match *piece {
+                Piece::Side((sx, sy), (dx, dy), angle) => (sx + dx * left, sy + dy * left, angle),
+                Piece::Corner((ox, oy), start) => {
+                    let angle = if radius > 0.0 { start + left / radius } else { start };
+                    (ox + radius * angle.cos(), oy + radius * angle.sin(), angle)
+                }
+            };
         }
+        (cx, y + h, FRAC_PI_2)
     }
 
-    fn create_player_view() -> (GtkBox, (Image, DrawingArea, Label, Label, Label, Label, Label, DrawingArea, ScrolledWindow, GtkBox, Button, Button, Button, Scale, Label)) {
+    fn create_player_view() -> (GtkBox, (Image, DrawingArea, Label, Label, Label, Label, Label, DrawingArea, ScrolledWindow, GtkBox, Button, Button, Button, Scale, Label, Label, Button, Label, VisualizerSlots)) {
         let player_box = GtkBox::new(Orientation::Vertical, 12);
         player_box.set_margin_start(20);
         player_box.set_margin_end(20);
         player_box.set_margin_top(15);
         player_box.set_margin_bottom(20);
 
-        // Horizontal row: [cava bars | album art]
+        // Horizontal row: [spacer | album art | spacer], with room under it;
+        // the visualizer draws over all of it, and its layout shows the
+        // spacers it needs (see apply_visualizer_layout)
         let art_row = GtkBox::new(Orientation::Horizontal, 0);
         art_row.set_halign(Align::Center);
 
-        // CAVA visualizer drawing area — outside, left of album art
-        let cava_area = DrawingArea::new();
-        cava_area.set_size_request(48, 210);
-        cava_area.set_app_paintable(true);
-        cava_area.style_context().add_class("cava-area");
-        art_row.pack_start(&cava_area, false, false, 0);
+        let left_spacer = DrawingArea::new();
+        left_spacer.set_size_request(VISUALIZER_SIDE_DEPTH as i32, 1);
+        left_spacer.set_no_show_all(true);
+        art_row.pack_start(&left_spacer, false, false, 0);
 
         // Album art
         let album_art_frame = GtkBox::new(Orientation::Horizontal, 0);
@@ -539,14 +1116,42 @@ impl MusicPlayerWindow {
         album_art.set_size_request(210, 210);
         album_art_frame.pack_start(&album_art, true, true, 0);
 
-        art_row.pack_start(&album_art_frame, false, false, 0);
+        // Takes the clicks that switch the visualizer layout
+        let art_events = gtk::EventBox::new();
+        art_events.add(&album_art_frame);
+        art_row.pack_start(&art_events, false, false, 0);
 
-        // Invisible spacer to balance the CAVA area and keep art centered
+        // Invisible spacer to balance the left one and keep art centered
         let right_spacer = DrawingArea::new();
-        right_spacer.set_size_request(48, 1);
+        right_spacer.set_size_request(VISUALIZER_SIDE_DEPTH as i32, 1);
+        right_spacer.set_no_show_all(true);
         art_row.pack_start(&right_spacer, false, false, 0);
 
-        player_box.pack_start(&art_row, false, false, 0);
+        let under_spacer = DrawingArea::new();
+        under_spacer.set_size_request(1, (VISUALIZER_UNDER_GAP + VISUALIZER_UNDER_DEPTH) as i32);
+        under_spacer.set_no_show_all(true);
+
+        let art_column = GtkBox::new(Orientation::Vertical, 0);
+        art_column.pack_start(&art_row, false, false, 0);
+        art_column.pack_start(&under_spacer, false, false, 0);
+
+        // CAVA visualizer drawing area — on top, letting clicks through
+        let cava_area = DrawingArea::new();
+        cava_area.set_app_paintable(true);
+        cava_area.style_context().add_class("cava-area");
+        let art_overlay = gtk::Overlay::new();
+        art_overlay.add(&art_column);
+        art_overlay.add_overlay(&cava_area);
+        art_overlay.set_overlay_pass_through(&cava_area, true);
+        player_box.pack_start(&art_overlay, false, false, 0);
+
+        let visualizer_slots = VisualizerSlots {
+            art_events,
+            art_frame: album_art_frame.clone(),
+            art_row: art_row.clone(),
+            side_spacers: [left_spacer, right_spacer],
+            under_spacer,
+        };
 
         // Song info
         let song_title = Label::new(Some("No song playing"));
@@ -573,6 +1178,13 @@ impl MusicPlayerWindow {
         song_album.set_halign(Align::Center);
         player_box.pack_start(&song_album, false, false, 0);
 
+        // Tempo and key, once the track has been analysed
+        let song_details = Label::new(None);
+        song_details.style_context().add_class("song-details");
+        song_details.set_halign(Align::Center);
+        song_details.set_no_show_all(true);
+        player_box.pack_start(&song_details, false, false, 0);
+
         // Waveform progress bar
         let waveform_area = DrawingArea::new();
         waveform_area.set_size_request(280, 48);
@@ -583,10 +1195,17 @@ impl MusicPlayerWindow {
             gdk::EventMask::BUTTON_PRESS_MASK
             | gdk::EventMask::BUTTON_RELEASE_MASK
             | gdk::EventMask::POINTER_MOTION_MASK
+            | gdk::EventMask::LEAVE_NOTIFY_MASK
+            | gdk::EventMask::SCROLL_MASK
+            | gdk::EventMask::SMOOTH_SCROLL_MASK
+            | gdk::EventMask::KEY_PRESS_MASK
         );
+        // Focusable so the arrow keys can fine-seek after a click
+        waveform_area.set_can_focus(true);
 
+        // Stretches with the window; the waveform re-bins to the new width
         let progress_container = GtkBox::new(Orientation::Vertical, 4);
-        progress_container.set_halign(Align::Center);
+        progress_container.set_halign(Align::Fill);
         progress_container.pack_start(&waveform_area, false, false, 0);
         
         // Time labels below waveform
@@ -665,6 +1284,17 @@ impl MusicPlayerWindow {
 
         player_box.pack_start(&volume_box, false, false, 0);
 
+        // Lyrics status line — sync-mode hints and short confirmations
+        let lyrics_status = Label::new(None);
+        lyrics_status.style_context().add_class("lyrics-status");
+        lyrics_status.set_line_wrap(true);
+        lyrics_status.set_line_wrap_mode(gtk::pango::WrapMode::WordChar);
+        lyrics_status.set_justify(gtk::Justification::Center);
+        lyrics_status.set_halign(Align::Center);
+        lyrics_status.set_no_show_all(true);
+        lyrics_status.hide();
+        player_box.pack_start(&lyrics_status, false, false, 0);
+
         // Synced lyrics view — fills remaining space below controls
         let lyrics_scroll = ScrolledWindow::new(None::<&gtk::Adjustment>, None::<&gtk::Adjustment>);
         lyrics_scroll.set_policy(PolicyType::Never, PolicyType::External);
@@ -682,6 +1312,15 @@ impl MusicPlayerWindow {
         lyrics_scroll.hide();
         player_box.pack_start(&lyrics_scroll, true, true, 0);
 
+        // Shown while auto-follow is paused because the user scrolled the lyrics
+        let lyrics_resume_btn = Button::with_label("Back to current line");
+        lyrics_resume_btn.style_context().add_class("lyrics-resume");
+        lyrics_resume_btn.set_halign(Align::Center);
+        lyrics_resume_btn.set_margin_bottom(8);
+        lyrics_resume_btn.set_no_show_all(true);
+        lyrics_resume_btn.hide();
+        player_box.pack_start(&lyrics_resume_btn, false, false, 0);
+
         (player_box, (
             album_art,
             cava_area,
@@ -698,6 +1337,10 @@ impl MusicPlayerWindow {
             next_btn,
             volume_scale,
             volume_percent,
+            lyrics_status,
+            lyrics_resume_btn,
+            song_details,
+            visualizer_slots,
         ))
     }
 
@@ -850,25 +1493,25 @@ impl MusicPlayerWindow {
     fn connect_signals(&mut self) {
         // ... (unchanged signal connections for tabs, controls, seek, volume, queue)
         // Tab switching
-        let stack_clone = self.stack.clone();
-        let player_tab_clone = self.player_tab.clone();
-        let library_tab_clone = self.library_tab.clone();
-        
-        self.player_tab.connect_clicked(move |_| {
-            stack_clone.set_visible_child_name("player");
-            player_tab_clone.style_context().add_class("active");
-            library_tab_clone.style_context().remove_class("active");
-        });
-
-        let stack_clone = self.stack.clone();
-        let player_tab_clone = self.player_tab.clone();
-        let library_tab_clone = self.library_tab.clone();
-        
-        self.library_tab.connect_clicked(move |_| {
-            stack_clone.set_visible_child_name("library");
-            library_tab_clone.style_context().add_class("active");
-            player_tab_clone.style_context().remove_class("active");
-        });
+        let tabs = [
+            (self.player_tab.clone(), "player"),
+            (self.lyrics_tab.clone(), "lyrics"),
+            (self.library_tab.clone(), "library"),
+        ];
+        for (tab, page) in tabs.clone() {
+            let stack_clone = self.stack.clone();
+            let tabs = tabs.clone();
+            tab.connect_clicked(move |clicked| {
+                stack_clone.set_visible_child_name(page);
+                for (other, _) in &tabs {
+                    if other == clicked {
+                        other.style_context().add_class("active");
+                    } else {
+                        other.style_context().remove_class("active");
+                    }
+                }
+            });
+        }
 
         // Playback controls
         let mpd_clone = self.mpd.clone();
@@ -900,41 +1543,170 @@ impl MusicPlayerWindow {
         // Waveform draw handler
         let wf_peaks = self.waveform_peaks.clone();
         let wf_pos = self.waveform_position.clone();
-        self.waveform_area.connect_draw(move |_widget, cr| {
-            let w = _widget.allocated_width() as f64;
-            let h = _widget.allocated_height() as f64;
+        let wf_hover = self.waveform_hover.clone();
+        let wf_style = self.waveform_style;
+        let wf_palette = self.bg_palette.clone();
+        let wf_clock = self.playback_clock.clone();
+        let wf_seeking = self.is_seeking.clone();
+        let wf_sections = self.waveform_sections.clone();
+        let wf_spectrogram = self.waveform_spectrogram.clone();
+        let wf_mode = self.seek_bar_mode.clone();
+        self.waveform_area.connect_draw(move |widget, cr| {
+            let w = widget.allocated_width() as f64;
+            let h = widget.allocated_height() as f64;
+            let colors = WaveformColors::from_palette(&wf_palette.borrow());
             let peaks = wf_peaks.borrow();
-            let pos = *wf_pos.borrow();
-            if peaks.is_empty() {
-                waveform::draw_placeholder(cr, w, h);
+            // Interpolate between polls so the playhead glides; a drag shows the drag position
+            let pos = match wf_clock.borrow().fraction() {
+                Some(fraction) if !*wf_seeking.borrow() => fraction,
+                _ => *wf_pos.borrow(),
+            };
+            // Until its spectrogram is decoded a track shows its waveform
+            let mut spectrogram = wf_spectrogram.borrow_mut();
+            let surface = spectrogram.as_mut()
+                .filter(|_| *wf_mode.borrow() == SeekBarMode::Spectrogram)
+                .and_then(|s| s.surface(&wf_palette.borrow()));
+            if let Some(surface) = surface {
+                spectrogram::draw_spectrogram(cr, surface, pos, w, h, &colors);
+            } else if peaks.is_empty() {
+                waveform::draw_placeholder(cr, w, h, &wf_style, &colors);
             } else {
-                waveform::draw_waveform(cr, &peaks, pos, w, h);
+                waveform::draw_waveform(cr, &peaks, pos, w, h, &wf_style, &colors);
+                if let Some(ref sections) = *wf_sections.borrow() {
+                    waveform::draw_sections(cr, sections, peaks.len(), w, h, &wf_style, &colors);
+                }
+            }
+            if let Some(x) = *wf_hover.borrow() {
+                waveform::draw_hover_line(cr, x, w, h, &colors);
             }
             glib::Propagation::Proceed
         });
 
+        // While playing, redraw only when the interpolated playhead has moved by half a pixel
+        let wf_clock = self.playback_clock.clone();
+        let last_cursor = Cell::new(-1.0_f64);
+        self.waveform_ticker.on_frame(move |area| {
+            let clock = *wf_clock.borrow();
+            let Some(fraction) = clock.fraction().filter(|_| clock.playing) else {
+                return false;
+            };
+            let x = fraction * area.allocated_width() as f64;
+            if (x - last_cursor.get()).abs() >= 0.5 {
+                last_cursor.set(x);
+                area.queue_draw();
+            }
+            true
+        });
+
+        // Fill whatever width we get: re-bin when the bar count changes
+        let wf_peaks = self.waveform_peaks.clone();
+        let wf_pyramid = self.waveform_pyramid.clone();
+        let wf_style = self.waveform_style;
+        self.waveform_area.connect_size_allocate(move |area, alloc| {
+            let bars = wf_style.bars_for_width(alloc.width());
+            if wf_pyramid.borrow().is_some() && bars != wf_peaks.borrow().len() {
+                Self::rebin_waveform(area, &wf_style, &wf_pyramid, &wf_peaks);
+            }
+        });
+
+        // Floating time (and lyric) preview that follows the pointer
+        let (preview_popup, preview_label) = Self::create_seek_preview();
+        let seek_preview: Rc<dyn Fn(f64)> = {
+            let area = self.waveform_area.clone();
+            let clock = self.playback_clock.clone();
+            let lyrics = self.current_lyrics.clone();
+            let sections = self.waveform_sections.clone();
+            let popup = preview_popup.clone();
+            Rc::new(move |x: f64| {
+                let duration = clock.borrow().duration;
+                Self::show_seek_preview(&popup, &preview_label, &area, x, duration, lyrics.borrow().as_ref(), sections.borrow().as_ref());
+            })
+        };
+
         // Seek via waveform click/drag
         let is_seeking_clone = self.is_seeking.clone();
         let wf_pos_for_press = self.waveform_position.clone();
         let wf_area_for_press = self.waveform_area.clone();
+        let preview_for_press = seek_preview.clone();
+        let seek_bar_mode = self.seek_bar_mode.clone();
         self.waveform_area.connect_button_press_event(move |widget, event| {
+            // Right-click switches between waveform and spectrogram
+            if event.button() == 3 {
+                let mode = seek_bar_mode.borrow().toggle();
+                *seek_bar_mode.borrow_mut() = mode;
+                widget.queue_draw();
+                return glib::Propagation::Stop;
+            }
             *is_seeking_clone.borrow_mut() = true;
+            widget.grab_focus();
             let w = widget.allocated_width() as f64;
             let pos = (event.position().0 / w).clamp(0.0, 1.0);
             *wf_pos_for_press.borrow_mut() = pos;
+            preview_for_press(event.position().0);
             wf_area_for_press.queue_draw();
             glib::Propagation::Proceed
         });
 
         let is_seeking_motion = self.is_seeking.clone();
         let wf_pos_for_motion = self.waveform_position.clone();
+        let wf_hover_motion = self.waveform_hover.clone();
         let wf_area_for_motion = self.waveform_area.clone();
+        let preview_for_motion = seek_preview.clone();
         self.waveform_area.connect_motion_notify_event(move |widget, event| {
+            *wf_hover_motion.borrow_mut() = Some(event.position().0);
+            preview_for_motion(event.position().0);
             if *is_seeking_motion.borrow() {
                 let w = widget.allocated_width() as f64;
                 let pos = (event.position().0 / w).clamp(0.0, 1.0);
                 *wf_pos_for_motion.borrow_mut() = pos;
-                wf_area_for_motion.queue_draw();
+            }
+            wf_area_for_motion.queue_draw();
+            glib::Propagation::Proceed
+        });
+
+        let wf_hover_leave = self.waveform_hover.clone();
+        let is_seeking_leave = self.is_seeking.clone();
+        let popup_for_leave = preview_popup.clone();
+        self.waveform_area.connect_leave_notify_event(move |widget, _| {
+            *wf_hover_leave.borrow_mut() = None;
+            // A drag keeps its preview until the button is released
+            if !*is_seeking_leave.borrow() {
+                popup_for_leave.hide();
+            }
+            widget.queue_draw();
+            glib::Propagation::Proceed
+        });
+
+        // Fine seeking: scroll wheel anywhere over the waveform, arrow keys once it has focus
+        let nudge = self.waveform_nudge_handler(&seek_preview, &preview_popup);
+        let nudge_for_scroll = nudge.clone();
+        self.waveform_area.connect_scroll_event(move |_, event| {
+            let shift = event.state().contains(gdk::ModifierType::SHIFT_MASK);
+            let step = if shift { FINE_SEEK_STEP * 5.0 } else { FINE_SEEK_STEP };
+            let direction = match event.direction() {
+                gdk::ScrollDirection::Up | gdk::ScrollDirection::Right => 1.0,
+                gdk::ScrollDirection::Down | gdk::ScrollDirection::Left => -1.0,
+                gdk::ScrollDirection::Smooth => {
+                    let (dx, dy) = event.scroll_deltas().unwrap_or((0.0, 0.0));
+                    let delta = if dy != 0.0 { -dy } else { dx };
+                    if delta > 0.0 { 1.0 } else if delta < 0.0 { -1.0 } else { 0.0 }
+                }
+                _ => return glib::Propagation::Proceed,
+            };
+            if direction != 0.0 {
+                nudge_for_scroll(direction * step);
+            }
+            glib::Propagation::Stop
+        });
+
+        self.waveform_area.connect_key_press_event(move |_, event| {
+            use gdk::keys::constants as key;
+            let shift = event.state().contains(gdk::ModifierType::SHIFT_MASK);
+            let step = if shift { FINE_SEEK_STEP * 5.0 } else { FINE_SEEK_STEP };
+            let keyval = event.keyval();
+            if keyval == key::Left || keyval == key::Right {
+                nudge(if keyval == key::Left { -step } else { step });
+                return glib::Propagation::Stop;
             }
             glib::Propagation::Proceed
         });
@@ -942,14 +1714,29 @@ impl MusicPlayerWindow {
         let is_seeking_clone = self.is_seeking.clone();
         let mpd_clone = self.mpd.clone();
         let wf_pos_for_release = self.waveform_position.clone();
-        self.waveform_area.connect_button_release_event(move |_, _| {
+        let clock_for_release = self.playback_clock.clone();
+        let hover_for_release = self.waveform_hover.clone();
+        let popup_for_release = preview_popup.clone();
+        self.waveform_area.connect_button_release_event(move |_, event| {
+            if event.button() == 3 {
+                return glib::Propagation::Stop;
+            }
             *is_seeking_clone.borrow_mut() = false;
+            // Dropped outside the waveform: nothing left to preview
+            if hover_for_release.borrow().is_none() {
+                popup_for_release.hide();
+            }
             let pos = *wf_pos_for_release.borrow();
             if let Ok(mut mpd) = mpd_clone.try_borrow_mut() {
                 if let Ok(status) = mpd.status() {
                     if let Some(duration) = status.duration {
                         let seek_time = pos * duration.as_secs_f64();
-                        let _ = mpd.seek(Duration::from_secs_f64(seek_time));
+                        if mpd.seek(Duration::from_secs_f64(seek_time)).is_ok() {
+                            // Keep the playhead where it was dropped until the next poll
+                            let mut clock = clock_for_release.borrow_mut();
+                            clock.elapsed = seek_time;
+                            clock.sampled_at = Instant::now();
+                        }
                     }
                 }
             }
@@ -1060,61 +1847,375 @@ impl MusicPlayerWindow {
         });
     }
 
-    fn play_folder(mpd: &Rc<RefCell<MPDClient>>, queue_store: &ListStore, folder_path: &str) {
-        use std::process::Command;
-
-        if let Ok(mut mpd_client) = mpd.try_borrow_mut() {
-            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-            let music_dir = PathBuf::from(&home).join("Music");
-            
-            // Get relative path from music directory
-            let relative_folder = PathBuf::from(folder_path)
-                .strip_prefix(&music_dir)
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default();
+    /// Keyboard shortcuts for the Now Playing view.
+    /// `+`/`-` shift the lyrics timing by 100 ms and `0` resets it; the offset is
+    /// saved per song. `t` cycles original / translation / both when a
+    /// translation track is loaded. Ctrl+L opens the tap-to-sync lyrics editor. While it is open, Space stamps
+    /// the current line, ←/→ nudge the last stamp by 100 ms, Backspace undoes a
+    /// stamp, Ctrl+S writes the `.lrc` and Esc (or Ctrl+L again) cancels.
+    fn connect_shortcuts(&self) {
+        use gdk::keys::constants as key;
 
-            let _ = mpd_client.clear();
+        let mpd_clone = self.mpd.clone();
+        let stack_clone = self.stack.clone();
+        let lyrics_sync = self.lyrics_sync.clone();
+        let current_lyrics = self.current_lyrics.clone();
+        let current_lyrics_index = self.current_lyrics_index.clone();
+        let current_song_info = self.current_song_info.clone();
+        let lyrics_box = self.lyrics_box.clone();
+        let lyrics_scroll = self.lyrics_scroll.clone();
+        let lyrics_status = self.lyrics_status.clone();
+        let lyrics_follow = self.lyrics_follow.clone();
+        let lyrics_resume_btn = self.lyrics_resume_btn.clone();
+        let lyrics_display = self.lyrics_display.clone();
+        let on_lyric_click = self.lyric_seek_handler();
+        let lyrics_tab = self.lyrics_tab.clone();
+        let karaoke_fullscreen = self.karaoke_fullscreen.clone();
+        let karaoke_ticker = self.karaoke_ticker.clone();
+        let track_info = self.track_info.clone();
+        let seek_bar_mode = self.seek_bar_mode.clone();
+        let waveform_area = self.waveform_area.clone();
+        let visualizer = self.visualizer.clone();
+        let visualizer_slots = self.visualizer_slots.clone();
+        let visualizer_layout = self.visualizer_layout.clone();
+        let cava_area = self.cava_area.clone();
+
+        // Full-screen karaoke hides the window chrome; follow the real window
+        // state so leaving full screen through the window manager restores it too
+        {
+            let chrome: Vec<gtk::Widget> = vec![
+                self.tabs_box.clone().upcast(),
+                self.queue_btn.clone().upcast(),
+                self.theme_toggle.clone().upcast(),
+            ];
+            let queue_revealer = self.queue_revealer.clone();
+            let karaoke_fullscreen = self.karaoke_fullscreen.clone();
+            self.window.connect_window_state_event(move |_, event| {
+                let fullscreen = event.new_window_state().contains(gdk::WindowState::FULLSCREEN);
+                if fullscreen != *karaoke_fullscreen.borrow() {
+                    *karaoke_fullscreen.borrow_mut() = fullscreen;
+                    for widget in &chrome {
+                        widget.set_visible(!fullscreen);
+                    }
+                    if fullscreen {
+                        queue_revealer.set_reveal_child(false);
+                    }
+                }
+                glib::Propagation::Proceed
+            });
+        }
 
-            // Add entire folder in one mpc call (much faster than per-song)
-            let _ = Command::new("mpc")
-                .args(&["-h", "127.0.0.1", "add", &relative_folder])
-                .output();
+        // Leave sync mode and put `lrc` (or nothing) back on screen
+        let restore_lyrics = {
+            let lyrics_sync = lyrics_sync.clone();
+            let current_lyrics = current_lyrics.clone();
+            let current_lyrics_index = current_lyrics_index.clone();
+            let lyrics_box = lyrics_box.clone();
+            let lyrics_scroll = lyrics_scroll.clone();
+            let lyrics_display = lyrics_display.clone();
+            let on_lyric_click = on_lyric_click.clone();
+            move |lrc: Option<LRCParser>| {
+                *lyrics_sync.borrow_mut() = None;
+                *current_lyrics_index.borrow_mut() = None;
+                Self::show_lyrics(&lyrics_box, &lyrics_scroll, lrc.as_ref(), *lyrics_display.borrow(), &on_lyric_click);
+                *current_lyrics.borrow_mut() = lrc;
+            }
+        };
 
-            let _ = mpd_client.shuffle();
-            let _ = mpd_client.play();
+        self.window.connect_key_press_event(move |window, event| {
+            // Leave typing in search boxes alone
+            if window.focused_widget().is_some_and(|w| w.is::<gtk::Entry>()) {
+                return glib::Propagation::Proceed;
+            }
+            let page = stack_clone.visible_child_name();
+            let on_lyrics_page = page.as_deref() == Some("lyrics");
+            if page.as_deref() != Some("player") && !on_lyrics_page {
+                return glib::Propagation::Proceed;
+            }
+            // Offset, display and full-screen changes redraw the page (next frame, after this handler)
+            if on_lyrics_page {
+                karaoke_ticker.start();
+            }
 
-            queue_store.clear();
-            let files: Vec<(String, String, String)> = if let Ok(songs) = mpd_client.get_queue() {
-                songs.iter().map(|s| (
-                    s.title.as_deref().unwrap_or("Unknown").to_string(),
-                    s.artist.as_deref().unwrap_or("Unknown").to_string(),
-                    s.file.clone(),
-                )).collect()
-            } else { vec![] };
+            let keyval = event.keyval().to_lower();
+            let ctrl = event.state().contains(gdk::ModifierType::CONTROL_MASK);
+            let syncing = lyrics_sync.borrow().is_some();
+            let (artist, title) = current_song_info.borrow().clone();
 
-            // Fast: populate text only
-            for (title, artist, _) in &files {
-                let iter = queue_store.append();
-                queue_store.set_value(&iter, 0, &title.to_value());
-                queue_store.set_value(&iter, 1, &artist.to_value());
-                queue_store.set_value(&iter, 3, &false.to_value());
+            // f / F11: full-screen karaoke; Esc also leaves it
+            let fullscreen = *karaoke_fullscreen.borrow();
+            if (!ctrl && (keyval == key::f || keyval == key::F11)) || (fullscreen && !syncing && keyval == key::Escape) {
+                if fullscreen {
+                    window.unfullscreen();
+                } else {
+                    lyrics_tab.clicked();
+                    window.fullscreen();
+                }
+                return glib::Propagation::Stop;
             }
 
-            // Lazy art loading via idle
-            let store = queue_store.clone();
-            let file_list: Vec<String> = files.into_iter().map(|(_, _, f)| f).collect();
-            let idx = Rc::new(RefCell::new(0usize));
-            let cache: Rc<RefCell<HashMap<String, Option<String>>>> = Rc::new(RefCell::new(HashMap::new()));
-            glib::idle_add_local(move || {
-                let i = *idx.borrow();
-                if i >= file_list.len() {
-                    return glib::ControlFlow::Break;
+            // The sync editor lives in the Now Playing lyrics pane
+            if ctrl && keyval == key::l && !on_lyrics_page {
+                if syncing {
+                    restore_lyrics(Self::load_lyrics(&artist, &title));
+                    lyrics_status.hide();
+                    return glib::Propagation::Stop;
                 }
-                if let Some(iter) = store.iter_nth_child(None, i as i32) {
-                    if let Some(art_path) = Self::find_album_art_cached(&file_list[i], &cache) {
-                        if let Ok(pb) = Pixbuf::from_file_at_scale(&art_path, 45, 45, true) {
-                            store.set_value(&iter, 2, &pb.to_value());
-                        }
+                if title.is_empty() {
+                    return glib::Propagation::Stop;
+                }
+                // Prefer plain lyrics; otherwise re-time the existing LRC
+                let session = LyricsSync::from_plain_file(lyrics::lyrics_path(&artist, &title, "txt"))
+                    .or_else(|| current_lyrics.borrow().as_ref().and_then(LyricsSync::from_lrc));
+                match session {
+                    Some(sync) => {
+                        Self::populate_lyrics_box(&lyrics_box, &sync.display_lines(), *lyrics_display.borrow(), &on_lyric_click);
+                        lyrics_scroll.show();
+                        *current_lyrics_index.borrow_mut() = None;
+                        *current_lyrics.borrow_mut() = Some(sync.preview());
+                        Self::mark_sync_cursor(&lyrics_box, sync.cursor());
+                        lyrics_status.set_text(&Self::sync_status_text(&sync));
+                        lyrics_status.show();
+                        *lyrics_sync.borrow_mut() = Some(sync);
+                    }
+                    None => {
+                        let msg = format!("No plain lyrics to sync — add \"{} - {}.txt\" to the lyrics folder", artist, title);
+                        Self::flash_lyrics_status(&lyrics_status, &msg);
+                    }
+                }
+                return glib::Propagation::Stop;
+            }
+
+            // i: loudness panel for the current track
+            if !ctrl && !syncing && !on_lyrics_page && keyval == key::i {
+                if track_info.is_visible() {
+                    track_info.popdown();
+                } else if !title.is_empty() {
+                    track_info.popup();
+                }
+                return glib::Propagation::Stop;
+            }
+
+            // s: waveform or spectrogram in the seek bar
+            if !ctrl && !syncing && !on_lyrics_page && keyval == key::s {
+                let mode = seek_bar_mode.borrow().toggle();
+                *seek_bar_mode.borrow_mut() = mode;
+                waveform_area.queue_draw();
+                return glib::Propagation::Stop;
+            }
+
+            // v: next visualizer layout (beside, under or around the art)
+            if !ctrl && !syncing && !on_lyrics_page && keyval == key::v && visualizer.is_some() {
+                Self::cycle_visualizer_layout(&visualizer_slots, &visualizer_layout, &cava_area);
+                return glib::Propagation::Stop;
+            }
+
+            if !syncing && keyval == key::t {
+                let lyrics_ref = current_lyrics.borrow();
+                let Some(lrc) = lyrics_ref.as_ref() else {
+                    return glib::Propagation::Proceed;
+                };
+                if !lrc.has_translation() {
+                    Self::flash_lyrics_status(&lyrics_status, "No translation for this song");
+                    return glib::Propagation::Stop;
+                }
+                let display = lyrics_display.borrow().next();
+                *lyrics_display.borrow_mut() = display;
+                // Re-render; the next tick restores the highlight
+                *current_lyrics_index.borrow_mut() = None;
+                Self::populate_lyrics_box(&lyrics_box, &lrc.lines, display, &on_lyric_click);
+                Self::flash_lyrics_status(&lyrics_status, display.describe());
+                return glib::Propagation::Stop;
+            }
+
+            if !syncing {
+                let earlier = keyval == key::plus || keyval == key::equal || keyval == key::KP_Add;
+                let later = keyval == key::minus || keyval == key::KP_Subtract;
+                let reset = keyval == key::_0 || keyval == key::KP_0;
+                if !(earlier || later || reset) {
+                    return glib::Propagation::Proceed;
+                }
+                let mut lyrics_ref = current_lyrics.borrow_mut();
+                let Some(lrc) = lyrics_ref.as_mut() else {
+                    return glib::Propagation::Proceed;
+                };
+                lrc.offset_ms = if reset {
+                    0
+                } else if earlier {
+                    lrc.offset_ms + lyrics::OFFSET_STEP_MS
+                } else {
+                    lrc.offset_ms - lyrics::OFFSET_STEP_MS
+                };
+                if let Err(e) = lyrics::save_offset(&artist, &title, lrc.offset_ms) {
+                    log::warn!("Failed to save lyrics offset: {}", e);
+                }
+                // Drop the highlight so the next tick re-picks the line with the new offset
+                if let Some(old_idx) = current_lyrics_index.borrow_mut().take() {
+                    if let Some(line) = lrc.lines.get(old_idx) {
+                        Self::set_lyric_active(&lyrics_box, old_idx, line, false, *lyrics_display.borrow());
+                    }
+                }
+                let msg = match lrc.offset_ms {
+                    0 => "Lyrics offset: 0.0 s".to_string(),
+                    ms if ms > 0 => format!("Lyrics offset: +{:.1} s (earlier)", ms as f64 / 1000.0),
+                    ms => format!("Lyrics offset: {:.1} s (later)", ms as f64 / 1000.0),
+                };
+                Self::flash_lyrics_status(&lyrics_status, &msg);
+                return glib::Propagation::Stop;
+            }
+
+            if keyval == key::Escape {
+                restore_lyrics(Self::load_lyrics(&artist, &title));
+                lyrics_status.hide();
+            } else if ctrl && keyval == key::s {
+                let lrc = lyrics_sync.borrow().as_ref().and_then(|sync| sync.to_lrc(&artist, &title));
+                let Some(lrc) = lrc else {
+                    lyrics_status.set_text("Stamp every line before saving");
+                    return glib::Propagation::Stop;
+                };
+                match lrc.save(&artist, &title) {
+                    Ok(path) => {
+                        // Fresh timing makes any old offset override meaningless
+                        if let Err(e) = lyrics::clear_offset(&artist, &title) {
+                            log::warn!("Failed to clear lyrics offset: {}", e);
+                        }
+                        restore_lyrics(Some(lrc));
+                        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                        Self::flash_lyrics_status(&lyrics_status, &format!("Saved {}", name));
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to save lyrics for {} - {}: {}", artist, title, e);
+                        lyrics_status.set_text(&format!("Could not save lyrics: {}", e));
+                    }
+                }
+            } else if keyval == key::space || keyval == key::BackSpace || keyval == key::Left || keyval == key::Right {
+                let mut sync_ref = lyrics_sync.borrow_mut();
+                let Some(sync) = sync_ref.as_mut() else {
+                    return glib::Propagation::Stop;
+                };
+
+                // Drop the old highlight; the preview is rebuilt below
+                if let Some(old_idx) = current_lyrics_index.borrow_mut().take() {
+                    if let Some(line) = sync.display_lines().get(old_idx) {
+                        Self::set_lyric_active(&lyrics_box, old_idx, line, false, *lyrics_display.borrow());
+                    }
+                }
+
+                let mut stamped = None;
+                if keyval == key::space {
+                    let elapsed = mpd_clone.try_borrow_mut().ok()
+                        .and_then(|mut mpd| mpd.status().ok())
+                        .and_then(|status| status.elapsed)
+                        .map(|e| e.as_secs_f64());
+                    if let Some(time) = elapsed {
+                        stamped = sync.stamp(time);
+                    }
+                } else if keyval == key::BackSpace {
+                    sync.unstamp();
+                } else {
+                    let step = if keyval == key::Left { -lyrics_sync::NUDGE_STEP } else { lyrics_sync::NUDGE_STEP };
+                    sync.nudge(step);
+                }
+
+                *current_lyrics.borrow_mut() = Some(sync.preview());
+                Self::mark_sync_cursor(&lyrics_box, sync.cursor());
+                lyrics_status.set_text(&Self::sync_status_text(sync));
+
+                // A fresh stamp is by definition the line being sung right now
+                if let Some(idx) = stamped {
+                    if let Some(line) = sync.display_lines().get(idx) {
+                        if let Some(child) = Self::set_lyric_active(&lyrics_box, idx, line, true, *lyrics_display.borrow()) {
+                            Self::resume_lyrics_follow(&lyrics_follow, &lyrics_resume_btn);
+                            Self::scroll_lyrics_to(&lyrics_scroll, &child, &lyrics_follow);
+                        }
+                    }
+                    *current_lyrics_index.borrow_mut() = Some(idx);
+                }
+            } else {
+                return glib::Propagation::Proceed;
+            }
+            glib::Propagation::Stop
+        });
+    }
+
+    /// Flag the next line to be stamped in the sync editor.
+    fn mark_sync_cursor(lyrics_box: &GtkBox, cursor: usize) {
+        for (i, child) in lyrics_box.children().iter().enumerate() {
+            if i == cursor {
+                child.style_context().add_class("lyrics-sync-cursor");
+            } else {
+                child.style_context().remove_class("lyrics-sync-cursor");
+            }
+        }
+    }
+
+    fn sync_status_text(sync: &LyricsSync) -> String {
+        if sync.is_complete() {
+            format!("All {} lines stamped — Ctrl+S save · ←/→ ±100 ms · Backspace undo · Esc cancel", sync.len())
+        } else {
+            format!(
+                "Syncing line {}/{} — Space stamp · ←/→ ±100 ms · Backspace undo · Esc cancel",
+                sync.cursor() + 1,
+                sync.len()
+            )
+        }
+    }
+
+    fn play_folder(mpd: &Rc<RefCell<MPDClient>>, queue_store: &ListStore, folder_path: &str) {
+        use std::process::Command;
+
+        if let Ok(mut mpd_client) = mpd.try_borrow_mut() {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            let music_dir = PathBuf::from(&home).join("Music");
+            
+            // Get relative path from music directory
+            let relative_folder = PathBuf::from(folder_path)
+                .strip_prefix(&music_dir)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let _ = mpd_client.clear();
+
+            // Add entire folder in one mpc call (much faster than per-song)
+            let _ = Command::new("mpc")
+                .args(&["-h", "127.0.0.1", "add", &relative_folder])
+                .output();
+
+            let _ = mpd_client.shuffle();
+            let _ = mpd_client.play();
+
+            queue_store.clear();
+            let files: Vec<(String, String, String)> = if let Ok(songs) = mpd_client.get_queue() {
+                songs.iter().map(|s| (
+                    s.title.as_deref().unwrap_or("Unknown").to_string(),
+                    s.artist.as_deref().unwrap_or("Unknown").to_string(),
+                    s.file.clone(),
+                )).collect()
+            } else { vec![] };
+
+            // Fast: populate text only
+            for (title, artist, _) in &files {
+                let iter = queue_store.append();
+                queue_store.set_value(&iter, 0, &title.to_value());
+                queue_store.set_value(&iter, 1, &artist.to_value());
+                queue_store.set_value(&iter, 3, &false.to_value());
+            }
+
+            // Lazy art loading via idle
+            let store = queue_store.clone();
+            let file_list: Vec<String> = files.into_iter().map(|(_, _, f)| f).collect();
+            let idx = Rc::new(RefCell::new(0usize));
+            let cache: Rc<RefCell<HashMap<String, Option<String>>>> = Rc::new(RefCell::new(HashMap::new()));
+            glib::idle_add_local(move || {
+                let i = *idx.borrow();
+                if i >= file_list.len() {
+                    return glib::ControlFlow::Break;
+                }
+                if let Some(iter) = store.iter_nth_child(None, i as i32) {
+                    if let Some(art_path) = Self::find_album_art_cached(&file_list[i], &cache) {
+                        if let Ok(pb) = Pixbuf::from_file_at_scale(&art_path, 45, 45, true) {
+                            store.set_value(&iter, 2, &pb.to_value());
+                        }
                     }
                 }
                 *idx.borrow_mut() = i + 1;
@@ -1262,6 +2363,20 @@ impl MusicPlayerWindow {
         let wf_pos_clone = self.waveform_position.clone();
         let wf_area_clone = self.waveform_area.clone();
         let wf_peaks_for_loop = self.waveform_peaks.clone();
+        let wf_pyramid_for_loop = self.waveform_pyramid.clone();
+        let wf_sections_for_loop = self.waveform_sections.clone();
+        let wf_spectrogram_for_loop = self.waveform_spectrogram.clone();
+        let track_analysis_for_loop = self.track_analysis.clone();
+        let song_details_clone = self.song_details.clone();
+        // (leading, trailing) silence already skipped, or passed, in this song
+        let silence_skipped = Rc::new(Cell::new((false, false)));
+        let wf_style = self.waveform_style;
+        let waveform_cache = self.waveform_cache.clone();
+        let waveform_jobs = WaveformJobs::new(waveform_cache.clone());
+        let art_cache_clone = self.art_cache.clone();
+        // Queue id of the song last prefetched, and what was computed for it
+        let prefetch_for: Rc<RefCell<Option<mpd::Id>>> = Rc::new(RefCell::new(None));
+        let prefetched: Rc<RefCell<Option<Prefetched>>> = Rc::new(RefCell::new(None));
         let play_btn_clone = self.play_btn.clone();
         let is_seeking_clone = self.is_seeking.clone();
         let current_song_file_clone = self.current_song_file.clone();
@@ -1276,9 +2391,21 @@ impl MusicPlayerWindow {
         let current_lyrics_index_clone = self.current_lyrics_index.clone();
         let lyrics_box_clone = self.lyrics_box.clone();
         let lyrics_scroll_clone = self.lyrics_scroll.clone();
-        let lyrics_scroll_target: Rc<RefCell<Option<f64>>> = Rc::new(RefCell::new(None));
-        let lyrics_scroll_target_clone = lyrics_scroll_target.clone();
-        let lyrics_scroll_for_anim = self.lyrics_scroll.clone();
+        let lyrics_follow = self.lyrics_follow.clone();
+        let lyrics_resume_btn = self.lyrics_resume_btn.clone();
+        let lyrics_status_clone = self.lyrics_status.clone();
+        let current_song_info_clone = self.current_song_info.clone();
+        let lyrics_sync_clone = self.lyrics_sync.clone();
+        let lyrics_display_clone = self.lyrics_display.clone();
+        let config_clone = self.config.clone();
+        let skip_silence = self.config.get_bool("playback", "skip_silence", false);
+        let playback_clock_clone = self.playback_clock.clone();
+        let waveform_ticker = self.waveform_ticker.clone();
+        let karaoke_ticker = self.karaoke_ticker.clone();
+        let lyrics_ticker = self.lyrics_ticker.clone();
+        let visualizer_clone = self.visualizer.clone();
+        let visualizer_activity_clone = self.visualizer_activity.clone();
+        let on_lyric_click = self.lyric_seek_handler();
 
         // Pre-render play/pause icon pixbufs once (avoid re-parsing SVG every 500ms)
         let play_pixbuf = load_icon_pixbuf(include_bytes!("assets/icons/media-playback-start-symbolic.svg"), 24, "#ffffff");
@@ -1298,6 +2425,7 @@ impl MusicPlayerWindow {
                     let mut last_st = last_play_state.borrow_mut();
                     if *last_st != Some(is_playing) {
                         *last_st = Some(is_playing);
+                        Self::update_visualizer_activity(&visualizer_clone, &visualizer_activity_clone, |a| a.playing = is_playing);
                         if is_playing {
                             if let Some(ref pb) = *pause_pb_clone {
                                 play_btn_clone.set_image(Some(&Image::from_pixbuf(Some(pb))));
@@ -1312,6 +2440,18 @@ impl MusicPlayerWindow {
                     if let (Some(elapsed), Some(duration)) = (status.elapsed, status.duration) {
                         let current = elapsed.as_secs_f64();
                         let total = duration.as_secs_f64();
+                        let previous = playback_clock_clone.replace(PlaybackClock {
+                            elapsed: current,
+                            sampled_at: Instant::now(),
+                            playing: is_playing,
+                            duration: total,
+                        });
+                        // Views that follow the clock animate while it runs and redraw once when it jumps
+                        if is_playing || previous.playing || previous.elapsed != current {
+                            waveform_ticker.start();
+                            karaoke_ticker.start();
+                            lyrics_ticker.start();
+                        }
 
                         time_label_clone.set_text(&format_time(current));
                         let remaining = total - current;
@@ -1322,64 +2462,40 @@ impl MusicPlayerWindow {
                             wf_area_clone.queue_draw();
                         }
 
+                        // Grace period after a manual scroll is over: glide back to the current line
+                        let follow_expired = lyrics_follow.borrow().paused_until.is_some_and(|t| Instant::now() >= t);
+                        if follow_expired {
+                            Self::resume_lyrics_follow(&lyrics_follow, &lyrics_resume_btn);
+                            if let Some(idx) = *current_lyrics_index_clone.borrow() {
+                                if let Some(child) = lyrics_box_clone.children().get(idx) {
+                                    Self::scroll_lyrics_to(&lyrics_scroll_clone, child, &lyrics_follow);
+                                }
+                            }
+                        }
+
                         // Sync lyrics highlight
                         if let Some(ref lrc) = *current_lyrics_clone.borrow() {
                             if let Some((idx, _text)) = lrc.get_current_line(current) {
                                 let mut last_idx = current_lyrics_index_clone.borrow_mut();
                                 if *last_idx != Some(idx) {
-                                    // Un-highlight old line — remove bold
-                                    if let Some(old_idx) = *last_idx {
-                                        if let Some(child) = lyrics_box_clone.children().get(old_idx) {
-                                            child.style_context().remove_class("lyrics-active");
-                                            child.style_context().add_class("lyrics-dim");
-                                            if let Some(lbl) = child.downcast_ref::<Label>() {
-                                                if let Some(ref line) = lrc.lines.get(old_idx) {
-                                                    let escaped = glib::markup_escape_text(&line.text);
-                                                    lbl.set_markup(&format!("<span size='medium'>{}</span>", escaped));
-                                                }
-                                            }
-                                        }
+                                    // Un-highlight old line — remove bold (before any, the first line is lit)
+                                    let old_idx = last_idx.unwrap_or(0);
+                                    if let Some(line) = lrc.lines.get(old_idx) {
+                                        Self::set_lyric_active(&lyrics_box_clone, old_idx, line, false, *lyrics_display_clone.borrow());
                                     }
-                                    // Highlight new line — set bold
-                                    let children = lyrics_box_clone.children();
-                                    if let Some(child) = children.get(idx) {
-                                        child.style_context().remove_class("lyrics-dim");
-                                        child.style_context().add_class("lyrics-active");
-                                        if let Some(lbl) = child.downcast_ref::<Label>() {
-                                            if let Some(ref line) = lrc.lines.get(idx) {
-                                                let escaped = glib::markup_escape_text(&line.text);
-                                                lbl.set_markup(&format!("<span size='medium' weight='bold'>{}</span>", escaped));
-                                            }
+                                    // Highlight new line — set bold, then smooth-scroll to it
+                                    if let Some(line) = lrc.lines.get(idx) {
+                                        if let Some(child) = Self::set_lyric_active(&lyrics_box_clone, idx, line, true, *lyrics_display_clone.borrow()) {
+                                            Self::scroll_lyrics_to(&lyrics_scroll_clone, &child, &lyrics_follow);
                                         }
-                                        // Smooth scroll — set target and start animation
-                                        let alloc = child.allocation();
-                                        let scroll_h = lyrics_scroll_clone.allocated_height() as f64;
-                                        let target = (alloc.y() as f64) - (scroll_h / 2.0) + (alloc.height() as f64 / 2.0);
-                                        *lyrics_scroll_target_clone.borrow_mut() = Some(target.max(0.0));
-                                        let scroll_anim = lyrics_scroll_for_anim.clone();
-                                        let target_anim = lyrics_scroll_target.clone();
-                                        glib::timeout_add_local(std::time::Duration::from_millis(16), move || {
-                                            let adj = scroll_anim.vadjustment();
-                                            let target_val = match *target_anim.borrow() {
-                                                Some(t) => t,
-                                                None => return glib::ControlFlow::Break,
-                                            };
-                                            let cur = adj.value();
-                                            let diff = target_val - cur;
-                                            if diff.abs() < 1.0 {
-                                                adj.set_value(target_val);
-                                                *target_anim.borrow_mut() = None;
-                                                return glib::ControlFlow::Break;
-                                            }
-                                            // Ease toward target (lerp 15% per frame)
-                                            adj.set_value(cur + diff * 0.15);
-                                            glib::ControlFlow::Continue
-                                        });
                                     }
                                     *last_idx = Some(idx);
                                 }
                             }
                         }
+                    } else {
+                        // Stopped: nothing to interpolate
+                        playback_clock_clone.borrow_mut().playing = false;
                     }
 
                     // Track current queue position and highlight it
@@ -1419,6 +2535,12 @@ impl MusicPlayerWindow {
                     
                     if file != *current_song_file_clone.borrow() {
                         *current_song_file_clone.borrow_mut() = file.clone();
+                        // Switching tracks cancels waveform prefetches; start the next one afresh
+                        *prefetch_for.borrow_mut() = None;
+                        let (ready_art, ready_lyrics) = match prefetched.borrow_mut().take() {
+                            Some(p) if p.file == file => (p.pixbuf.map(|pb| (pb, p.palette)), Some(p.lyrics)),
+                            _ => (None, None),
+                        };
                         
                         let title = song.title.as_deref().unwrap_or("Unknown");
                         let artist = song.artist.as_deref().unwrap_or("Unknown Artist");
@@ -1434,40 +2556,82 @@ impl MusicPlayerWindow {
                             song_album_clone.hide();
                         }
 
-                        // Extract waveform peaks in background thread
-                        {
-                            let wf_peaks = wf_peaks_for_loop.clone();
-                            let wf_area = wf_area_clone.clone();
-                            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-                            let full_path = PathBuf::from(&home).join("Music").join(&file);
-                            let full_path_str = full_path.to_string_lossy().to_string();
+                        // Waveform: straight from the disk cache for known tracks,
+                        // otherwise decode on the job pool (which caches the result)
+                        let wf_peaks = wf_peaks_for_loop.clone();
+                        let wf_area = wf_area_clone.clone();
+                        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+                        let full_path = PathBuf::from(&home).join("Music").join(&file);
+                        let wf_key = CacheKey::new(&file, &full_path);
+                        silence_skipped.set((false, false));
+                        let cached = wf_key.as_ref().and_then(|k| {
+                            Some((waveform_cache.load(k)?, waveform_cache.load_spectrogram(k)?, waveform_cache.load_analysis(k)?))
+                        });
+                        if let Some((profile, spectrogram, analysis)) = cached {
+                            waveform_jobs.cancel();
+                            *wf_sections_for_loop.borrow_mut() = Some(TrackSections::analyze(&profile.bins));
+                            *wf_spectrogram_for_loop.borrow_mut() = spectrogram.map(SpectrogramImage::new);
+                            Self::show_track_analysis(&song_details_clone, &track_analysis_for_loop, Some(analysis));
+                            *wf_pyramid_for_loop.borrow_mut() = Some(PeakPyramid::new(&profile));
+                            Self::rebin_waveform(&wf_area, &wf_style, &wf_pyramid_for_loop, &wf_peaks);
+                        } else {
                             // Clear current peaks immediately
-                            wf_peaks.borrow_mut().clear();
-                            wf_area.queue_draw();
-                            // Use a channel to send peaks back to main thread
-                            let (tx, rx) = std::sync::mpsc::channel::<Vec<PeakPair>>();
+                            *wf_pyramid_for_loop.borrow_mut() = None;
+                            *wf_sections_for_loop.borrow_mut() = None;
+                            *wf_spectrogram_for_loop.borrow_mut() = None;
+                            Self::show_track_analysis(&song_details_clone, &track_analysis_for_loop, None);
+                            Self::rebin_waveform(&wf_area, &wf_style, &wf_pyramid_for_loop, &wf_peaks);
+                            // Partial profiles stream in while decoding, then the final one;
+                            // the channel disconnects when the job ends
+                            let job = waveform_jobs.submit(full_path.to_string_lossy().to_string(), wf_key);
+                            let jobs = waveform_jobs.clone();
                             let wf_peaks_rx = wf_peaks.clone();
                             let wf_area_rx = wf_area.clone();
+                            let wf_pyramid_rx = wf_pyramid_for_loop.clone();
+                            let wf_sections_rx = wf_sections_for_loop.clone();
+                            let wf_spectrogram_rx = wf_spectrogram_for_loop.clone();
+                            let track_analysis_rx = track_analysis_for_loop.clone();
+                            let song_details_rx = song_details_clone.clone();
                             glib::timeout_add_local(std::time::Duration::from_millis(50), move || {
-                                match rx.try_recv() {
-                                    Ok(peaks) => {
-                                        *wf_peaks_rx.borrow_mut() = peaks;
-                                        wf_area_rx.queue_draw();
-                                        glib::ControlFlow::Break
+                                // A newer song owns the waveform now
+                                if !jobs.is_current(job.generation) {
+                                    return glib::ControlFlow::Break;
+                                }
+                                // Only the newest profile matters; skip any the UI fell behind on
+                                let mut latest = None;
+                                let mut done = false;
+                                loop {
+                                    match job.rx.try_recv() {
+                                        Ok(WaveformUpdate::Partial(profile)) => latest = Some(profile),
+                                        // Sections need the whole track; partial profiles end in empty bins
+                                        Ok(WaveformUpdate::Done(decoded)) => {
+                                            *wf_sections_rx.borrow_mut() = Some(TrackSections::analyze(&decoded.profile.bins));
+                                            *wf_spectrogram_rx.borrow_mut() = decoded.spectrogram.map(SpectrogramImage::new);
+                                            Self::show_track_analysis(&song_details_rx, &track_analysis_rx, Some(decoded.analysis));
+                                            latest = Some(decoded.profile);
+                                        }
+                                        Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                                        Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                                            done = true;
+                                            break;
+                                        }
                                     }
-                                    Err(std::sync::mpsc::TryRecvError::Empty) => glib::ControlFlow::Continue,
-                                    Err(_) => glib::ControlFlow::Break,
                                 }
-                            });
-                            std::thread::spawn(move || {
-                                // Target ~70 bars for a 280px wide area (bar=2px + gap=2px)
-                                if let Some(data) = WaveformData::from_file(&full_path_str, 70) {
-                                    let _ = tx.send(data.peaks);
+                                if let Some(profile) = latest {
+                                    *wf_pyramid_rx.borrow_mut() = Some(PeakPyramid::new(&profile));
+                                    Self::rebin_waveform(&wf_area_rx, &wf_style, &wf_pyramid_rx, &wf_peaks_rx);
                                 }
+                                if done { glib::ControlFlow::Break } else { glib::ControlFlow::Continue }
                             });
                         }
 
-                        if let Some(art_path) = Self::find_album_art(&file) {
+                        if let Some((pixbuf, palette)) = ready_art {
+                            album_art_clone.set_from_pixbuf(Some(&pixbuf));
+                            if let Some(palette) = palette {
+                                *bg_palette_clone.borrow_mut() = palette;
+                            }
+                            background_clone.queue_draw();
+                        } else if let Some(art_path) = Self::find_album_art(&file) {
                             let art_path_owned = art_path.clone();
                             let album_art_c = album_art_clone.clone();
                             let bg_palette_c = bg_palette_clone.clone();
@@ -1493,54 +2657,1165 @@ impl MusicPlayerWindow {
 
                         // Load synced lyrics from ~/Music/Lyrics/
                         {
-                            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-                            let lyrics_dir = PathBuf::from(&home).join("Music").join("Lyrics");
-                            // Try "Artist - Title.lrc"
-                            let lrc_path = lyrics_dir.join(format!("{} - {}.lrc", artist, title));
-                            // Clear old lyrics
-                            for child in lyrics_box_clone.children() {
-                                lyrics_box_clone.remove(&child);
+                            *current_song_info_clone.borrow_mut() = (artist.to_string(), title.to_string());
+                            // A sync session belongs to the song it was started on
+                            if lyrics_sync_clone.borrow_mut().take().is_some() {
+                                lyrics_status_clone.hide();
                             }
-                            *current_lyrics_clone.borrow_mut() = None;
                             *current_lyrics_index_clone.borrow_mut() = None;
-                            lyrics_scroll_clone.hide();
-
-                            if lrc_path.exists() {
-                                if let Some(lrc) = LRCParser::from_file(&lrc_path) {
-                                    for (i, line) in lrc.lines.iter().enumerate() {
-                                        let label = Label::new(None);
-                                        let escaped = glib::markup_escape_text(&line.text);
-                                        if line.text.is_empty() {
-                                            label.set_markup("<span size='small'> </span>");
-                                        } else {
-                                            label.set_markup(&format!(
-                                                "<span size='medium'>{}</span>", escaped
-                                            ));
-                                        }
-                                        label.set_line_wrap(true);
-                                        label.set_line_wrap_mode(gtk::pango::WrapMode::WordChar);
-                                        label.set_justify(gtk::Justification::Center);
-                                        label.set_halign(Align::Center);
-                                        label.set_margin_top(4);
-                                        label.set_margin_bottom(4);
-                                        label.style_context().add_class("lyrics-dim");
-                                        if i == 0 {
-                                            label.style_context().remove_class("lyrics-dim");
-                                            label.style_context().add_class("lyrics-active");
-                                        }
-                                        lyrics_box_clone.pack_start(&label, false, false, 0);
+                            // Stale animations would scroll the new song's lyrics to the old position
+                            lyrics_follow.borrow_mut().cancel_animation();
+                            Self::resume_lyrics_follow(&lyrics_follow, &lyrics_resume_btn);
+                            let lrc = ready_lyrics.unwrap_or_else(|| Self::load_lyrics(artist, title));
+                            // A downloaded .txt is waiting to be synced; don't fetch it again
+                            let missing = lrc.is_none() && !lyrics::lyrics_path(artist, title, "txt").exists();
+                            Self::show_lyrics(&lyrics_box_clone, &lyrics_scroll_clone, lrc.as_ref(), *lyrics_display_clone.borrow(), &on_lyric_click);
+                            *current_lyrics_clone.borrow_mut() = lrc;
+
+                            // Nothing on disk: ask the online providers, if the user enabled any
+                            let providers = lyrics_provider::providers(&config_clone);
+                            if missing && !providers.is_empty() {
+                                let query = LyricsQuery {
+                                    artist: artist.to_string(),
+                                    title: title.to_string(),
+                                    album: album.map(str::to_string),
+                                    duration: song.duration.map(|d| d.as_secs_f64()),
+                                };
+                                let (tx, rx) = std::sync::mpsc::channel::<PathBuf>();
+                                let song_file = current_song_file_clone.clone();
+                                let lyrics_box = lyrics_box_clone.clone();
+                                let lyrics_scroll = lyrics_scroll_clone.clone();
+                                let lyrics_status = lyrics_status_clone.clone();
+                                let current_lyrics = current_lyrics_clone.clone();
+                                let lyrics_display = lyrics_display_clone.clone();
+                                let on_click = on_lyric_click.clone();
+                                let karaoke_ticker = karaoke_ticker.clone();
+                                let fetched_for = file.clone();
+                                let (artist, title) = (artist.to_string(), title.to_string());
+                                glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
+                                    let path = match rx.try_recv() {
+                                        Ok(path) => path,
+                                        Err(std::sync::mpsc::TryRecvError::Empty) => return glib::ControlFlow::Continue,
+                                        Err(_) => return glib::ControlFlow::Break,
+                                    };
+                                    // The song may have changed while we were waiting on the network
+                                    if *song_file.borrow() != fetched_for || current_lyrics.borrow().is_some() {
+                                        return glib::ControlFlow::Break;
                                     }
-                                    lyrics_scroll_clone.show();
-                                    lyrics_box_clone.show_all();
-                                    *current_lyrics_clone.borrow_mut() = Some(lrc);
-                                }
+                                    if path.extension().is_some_and(|e| e == "txt") {
+                                        Self::flash_lyrics_status(&lyrics_status, "Downloaded plain lyrics — press Ctrl+L to sync them");
+                                    } else if let Some(lrc) = Self::load_lyrics(&artist, &title) {
+                                        Self::show_lyrics(&lyrics_box, &lyrics_scroll, Some(&lrc), *lyrics_display.borrow(), &on_click);
+                                        *current_lyrics.borrow_mut() = Some(lrc);
+                                        karaoke_ticker.start();
+                                    }
+                                    glib::ControlFlow::Break
+                                });
+                                std::thread::spawn(move || {
+                                    if let Some(path) = lyrics_provider::fetch_and_save(&providers, &query) {
+                                        let _ = tx.send(path);
+                                    }
+                                });
                             }
                         }
                     }
                 }
+
+                if let (true, Some(status)) = (skip_silence, status.as_ref()) {
+                    if let Some(ref track_sections) = *wf_sections_for_loop.borrow() {
+                        if !*is_seeking_clone.borrow() {
+                            Self::skip_silence(&mut mpd, status, track_sections, &silence_skipped, &playback_clock_clone);
+                        }
+                    }
+                }
+
+                // Warm the caches for the next queued song while this one plays
+                let next = status.as_ref().and_then(|s| s.nextsong);
+                if next.map(|p| p.id) != *prefetch_for.borrow() {
+                    *prefetch_for.borrow_mut() = next.map(|p| p.id);
+                    if let Some(Ok(Some(next_song))) = next.map(|p| mpd.song_at(p.pos)) {
+                        Self::prefetch_song(&next_song, &waveform_cache, &waveform_jobs, &art_cache_clone, &prefetched);
+                    }
+                }
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+
+    /// Seek past a long silent start, and go on to the next song once a long
+    /// silent end begins. Each happens once per song (`skipped` holds the
+    /// leading and trailing flags), so seeking back into the silence plays it.
+    fn skip_silence(
+        mpd: &mut MPDClient,
+        status: &mpd::Status,
+        track_sections: &TrackSections,
+        skipped: &Cell<(bool, bool)>,
+        clock: &RefCell<PlaybackClock>,
+    ) {
+        let (Some(elapsed), Some(duration)) = (status.elapsed, status.duration) else {
+            return;
+        };
+        if !matches!(status.state, mpd::State::Play) {
+            return;
+        }
+        let current = elapsed.as_secs_f64();
+        let total = duration.as_secs_f64();
+        let lead_end = track_sections.leading_silence * total;
+        let trail_start = track_sections.trailing_silence * total;
+        let (leading_done, mut trailing_done) = skipped.get();
+
+        if !leading_done && current < lead_end && lead_end >= sections::MIN_SKIPPABLE_SILENCE {
+            match mpd.seek(Duration::from_secs_f64(lead_end)) {
+                Ok(()) => clock.borrow_mut().elapsed = lead_end,
+                Err(e) => log::warn!("Failed to skip leading silence: {}", e),
+            }
+        }
+
+        if !trailing_done && current >= trail_start && total - trail_start >= sections::MIN_SKIPPABLE_SILENCE {
+            if let Err(e) = mpd.next() {
+                log::warn!("Failed to skip trailing silence: {}", e);
+            }
+            trailing_done = true;
+        }
+        skipped.set((true, trailing_done));
+    }
+
+    /// Prepare `song` in the background so switching to it is instant: decode its
+    /// waveform into the cache, resolve its art and palette, and parse its lyrics.
+    fn prefetch_song(
+        song: &mpd::Song,
+        waveform_cache: &WaveformCache,
+        waveform_jobs: &WaveformJobs,
+        art_cache: &Rc<RefCell<HashMap<String, Option<String>>>>,
+        prefetched: &Rc<RefCell<Option<Prefetched>>>,
+    ) {
+        let file = song.file.clone();
+        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        let full_path = PathBuf::from(&home).join("Music").join(&file);
+        if let Some(key) = CacheKey::new(&file, &full_path) {
+            if !waveform_cache.contains(&key) {
+                waveform_jobs.prefetch(full_path.to_string_lossy().to_string(), key);
+            }
+        }
+
+        let artist = song.artist.clone().unwrap_or_else(|| "Unknown Artist".to_string());
+        let title = song.title.clone().unwrap_or_else(|| "Unknown".to_string());
+        let (tx, rx) = std::sync::mpsc::channel();
+        let art_file = file.clone();
+        std::thread::spawn(move || {
+            let art_path = Self::resolve_album_art(&art_file);
+            let palette = art_path
+                .as_deref()
+                .and_then(ColorExtractor::extract_palette)
+                .map(|p| p.map(|c| (c.r, c.g, c.b)));
+            let lyrics = Self::load_lyrics(&artist, &title);
+            let _ = tx.send((art_path, palette, lyrics));
+        });
+
+        let art_cache = art_cache.clone();
+        let prefetched = prefetched.clone();
+        glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
+            let (art_path, palette, lyrics) = match rx.try_recv() {
+                Ok(result) => result,
+                Err(std::sync::mpsc::TryRecvError::Empty) => return glib::ControlFlow::Continue,
+                Err(_) => return glib::ControlFlow::Break,
+            };
+            // Pixbufs stay on the GTK thread; scaling one is quick next to the palette
+            let pixbuf = art_path
+                .as_ref()
+                .and_then(|p| Pixbuf::from_file_at_scale(p, 260, 260, true).ok());
+            art_cache.borrow_mut().insert(file.clone(), art_path);
+            *prefetched.borrow_mut() = Some(Prefetched { file: file.clone(), pixbuf, palette, lyrics });
+            glib::ControlFlow::Break
+        });
+    }
+
+    /// Karaoke highlight: for lyrics with word timing (TTML, VTT), light up
+    /// the active line word by word, interpolating between MPD polls. Runs
+    /// only while such a line is current, the pane is shown and MPD plays.
+    fn connect_karaoke_highlight(&self) {
+        let current_lyrics = self.current_lyrics.clone();
+        let current_lyrics_index = self.current_lyrics_index.clone();
+        let lyrics_box = self.lyrics_box.clone();
+        let lyrics_display = self.lyrics_display.clone();
+        let playback_clock = self.playback_clock.clone();
+
+        self.lyrics_ticker.on_frame(move |_| {
+            let Some(idx) = *current_lyrics_index.borrow() else {
+                return false;
+            };
+            let lyrics_ref = current_lyrics.borrow();
+            let Some(lrc) = lyrics_ref.as_ref() else {
+                return false;
+            };
+            let Some(line) = lrc.lines.get(idx).filter(|l| !l.words.is_empty()) else {
+                return false;
+            };
+            let clock = *playback_clock.borrow();
+            let now = clock.now() + lrc.offset_secs();
+            let sung = line.words.iter().take_while(|w| w.start <= now).count();
+            let markup = Self::karaoke_markup(line, true, *lyrics_display.borrow(), Some(sung));
+            if let Some(lbl) = lyrics_box.children().get(idx).and_then(Self::lyric_label) {
+                // Only touch the label when the sung-word count actually changed
+                if lbl.label() != markup {
+                    lbl.set_markup(&markup);
+                }
+            }
+            clock.playing
+        });
+    }
+
+    /// Draw the full-page lyrics view: palette gradient with the album art
+    /// blurred on top, large centered lines that dim with distance from the
+    /// current one, and a countdown through instrumental gaps.
+    fn connect_karaoke_view(&self) {
+        let current_lyrics = self.current_lyrics.clone();
+        let lyrics_display = self.lyrics_display.clone();
+        let playback_clock = self.playback_clock.clone();
+        let bg_palette = self.bg_palette.clone();
+        let album_art = self.album_art.clone();
+
+        // Smoothed scroll position, in lines; -1 is the slot before the first line
+        let focus = Rc::new(RefCell::new((-1.0_f64, Instant::now())));
+        let animating = Rc::new(RefCell::new(false));
+        let bg_cache: Rc<RefCell<Option<([(f64, f64, f64); 4], Option<Pixbuf>, i32, i32, cairo::ImageSurface)>>> =
+            Rc::new(RefCell::new(None));
+
+        let animating_draw = animating.clone();
+        let ticker = self.karaoke_ticker.clone();
+        self.karaoke_area.connect_draw(move |widget, cr| {
+            let w = widget.allocated_width();
+            let h = widget.allocated_height();
+            let pal = *bg_palette.borrow();
+            let art = album_art.pixbuf();
+
+            let stale = match bg_cache.borrow().as_ref() {
+                Some((cp, ca, cw, ch, _)) => *cp != pal || *ca != art || *cw != w || *ch != h,
+                None => true,
+            };
+            if stale {
+                if let Some(surf) = Self::render_karaoke_background(w, h, &pal, art.as_ref()) {
+                    *bg_cache.borrow_mut() = Some((pal, art, w, h, surf));
+                }
+            }
+            if let Some((_, _, _, _, ref surf)) = *bg_cache.borrow() {
+                cr.set_source_surface(surf, 0.0, 0.0).unwrap();
+                cr.paint().unwrap();
+            }
+
+            let (w, h) = (w as f64, h as f64);
+            let size = (w / 16.0).clamp(20.0, 64.0);
+            let lyrics_ref = current_lyrics.borrow();
+            let Some(lrc) = lyrics_ref.as_ref().filter(|l| !l.lines.is_empty()) else {
+                Self::draw_karaoke_text(cr, "No lyrics", w, h / 2.0, size * 0.6, 0.5, false);
+                *animating_draw.borrow_mut() = false;
+                return glib::Propagation::Stop;
+            };
+
+            let clock_now = playback_clock.borrow().now();
+            let now = clock_now + lrc.offset_secs();
+            let active = lrc.get_current_line(clock_now).map(|(i, _)| i);
+            let target = active.map_or(-1.0, |i| i as f64);
+
+            // Ease towards the active line; jump on seeks
+            let focus_now = {
+                let mut f = focus.borrow_mut();
+                let dt = f.1.elapsed().as_secs_f64();
+                f.0 = if (target - f.0).abs() > 8.0 {
+                    target
+                } else {
+                    f.0 + (target - f.0) * (1.0 - (-dt * 8.0).exp())
+                };
+                f.1 = Instant::now();
+                f.0
+            };
+            *animating_draw.borrow_mut() = (target - focus_now).abs() > 0.001;
+
+            let display = *lyrics_display.borrow();
+            let gap = size * 0.6;
+            let dot_slot = size * 1.2;
+
+            // Lay out the visible window of lines, centered on the focus
+            let first = (focus_now.floor() as isize - 8).max(-1);
+            let last = (focus_now.ceil() as isize + 8).min(lrc.lines.len() as isize - 1);
+            let mut slots = Vec::new();
+            for i in first..=last {
+                let distance = (i as f64 - focus_now).abs();
+                let scale = 1.0 - 0.2 * distance.min(1.0);
+                let line = (i >= 0).then(|| &lrc.lines[i as usize]);
+                let layout = line.filter(|l| !l.text.is_empty()).map(|line| {
+                    let is_active = active == Some(i as usize);
+                    let sung = is_active.then(|| line.words.iter().take_while(|wd| wd.start <= now).count());
+                    let markup = Self::karaoke_markup(line, is_active, display, sung);
+                    Self::karaoke_layout(cr, &markup, w, size * scale)
+                });
+                let height = layout.as_ref().map_or(dot_slot, |l| l.pixel_size().1 as f64);
+                slots.push((i, distance, layout, height));
+            }
+
+            let mut tops = Vec::with_capacity(slots.len());
+            let mut y = 0.0;
+            for slot in &slots {
+                tops.push(y);
+                y += slot.3 + gap;
+            }
+            let center = |k: usize| tops[k] + slots[k].3 / 2.0;
+            let k0 = (focus_now.floor() as isize - first).clamp(0, slots.len() as isize - 1) as usize;
+            let frac = focus_now - focus_now.floor();
+            let focus_y = match slots.get(k0 + 1) {
+                Some(_) => center(k0) + (center(k0 + 1) - center(k0)) * frac,
+                None => center(k0),
+            };
+            let offset = h * 0.45 - focus_y;
+
+            for (k, (i, distance, layout, height)) in slots.iter().enumerate() {
+                let top = tops[k] + offset;
+                if top + height < 0.0 || top > h {
+                    continue;
+                }
+                let alpha = (1.0 - 0.25 * distance).max(0.12);
+                if let Some(layout) = layout {
+                    cr.push_group();
+                    cr.set_source_rgb(1.0, 1.0, 1.0);
+                    cr.move_to(w * 0.08, top);
+                    pangocairo::functions::show_layout(cr, layout);
+                    cr.pop_group_to_source().unwrap();
+                    cr.paint_with_alpha(alpha).unwrap();
+                }
+                // The slot we're sitting in is an instrumental gap: count down to the next line
+                let in_gap = *i == target as isize && layout.is_none();
+                if in_gap {
+                    let gap_start = active.map_or(0.0, |a| lrc.lines[a].timestamp);
+                    let next = lrc.lines[(target + 1.0) as usize..].iter().find(|l| !l.text.is_empty());
+                    if let Some(next) = next {
+                        if next.timestamp - gap_start >= KARAOKE_COUNTDOWN_MIN_GAP && now < next.timestamp {
+                            let progress = ((now - gap_start) / (next.timestamp - gap_start)).clamp(0.0, 1.0);
+                            Self::draw_karaoke_countdown(cr, w, top + height / 2.0, size * 0.18, progress, next.timestamp - now);
+                        }
+                    }
+                }
+            }
+
+            // The scroll eases on after the clock stops (or after a jump while paused)
+            if *animating_draw.borrow() {
+                ticker.start();
+            }
+            glib::Propagation::Stop
+        });
+
+        // Redraw every frame while the page is shown and something moves; new
+        // art (and with it the palette) or a key press on the page needs one more
+        let playback_clock = self.playback_clock.clone();
+        self.karaoke_ticker.on_frame(move |area| {
+            area.queue_draw();
+            playback_clock.borrow().playing || *animating.borrow()
+        });
+        let ticker = self.karaoke_ticker.clone();
+        self.album_art.connect_notify_local(Some("pixbuf"), move |_, _| ticker.start());
+    }
+
+    /// Palette gradient with the album art scaled to cover, blurred and darkened
+    /// so large text stays readable. Blurring happens at 1/10 scale to keep it cheap.
+    fn render_karaoke_background(w: i32, h: i32, pal: &[(f64, f64, f64); 4], art: Option<&Pixbuf>) -> Option<cairo::ImageSurface> {
+        if w <= 0 || h <= 0 {
+            return None;
+        }
+        let surf = cairo::ImageSurface::create(cairo::Format::ARgb32, w, h).ok()?;
+        let cr = cairo::Context::new(&surf).ok()?;
+        paint_palette_gradient(&cr, w as f64, h as f64, pal);
+
+        if let Some(art) = art {
+            let (sw, sh) = ((w / 10).max(1), (h / 10).max(1));
+            let mut small = cairo::ImageSurface::create(cairo::Format::ARgb32, sw, sh).ok()?;
+            {
+                let sc = cairo::Context::new(&small).ok()?;
+                let cover = (sw as f64 / art.width() as f64).max(sh as f64 / art.height() as f64);
+                sc.translate(
+                    (sw as f64 - art.width() as f64 * cover) / 2.0,
+                    (sh as f64 - art.height() as f64 * cover) / 2.0,
+                );
+                sc.scale(cover, cover);
+                sc.set_source_pixbuf(art, 0.0, 0.0);
+                sc.paint().unwrap();
+            }
+            if let Some(blurred) = blur_surface(&mut small, 4, 3) {
+                cr.save().unwrap();
+                cr.scale(w as f64 / sw as f64, h as f64 / sh as f64);
+                cr.set_source_surface(&blurred, 0.0, 0.0).unwrap();
+                cr.source().set_filter(cairo::Filter::Bilinear);
+                cr.paint_with_alpha(0.5).unwrap();
+                cr.restore().unwrap();
+            }
+        }
+
+        cr.set_source_rgba(0.0, 0.0, 0.0, 0.35);
+        cr.paint().unwrap();
+        drop(cr);
+        Some(surf)
+    }
+
+    /// Centered, wrapping Pango layout for one karaoke line at `size` px.
+    fn karaoke_layout(cr: &cairo::Context, markup: &str, w: f64, size: f64) -> gtk::pango::Layout {
+        let layout = pangocairo::functions::create_layout(cr);
+        let mut font = gtk::pango::FontDescription::from_string("Sans");
+        font.set_absolute_size(size * gtk::pango::SCALE as f64);
+        layout.set_font_description(Some(&font));
+        layout.set_width((w * 0.84 * gtk::pango::SCALE as f64) as i32);
+        layout.set_wrap(gtk::pango::WrapMode::WordChar);
+        layout.set_alignment(gtk::pango::Alignment::Center);
+        layout.set_markup(markup);
+        layout
+    }
+
+    fn draw_karaoke_text(cr: &cairo::Context, text: &str, w: f64, cy: f64, size: f64, alpha: f64, bold: bool) {
+        let weight = if bold { " weight='bold'" } else { "" };
+        let markup = format!("<span{}>{}</span>", weight, glib::markup_escape_text(text));
+        let layout = Self::karaoke_layout(cr, &markup, w, size);
+        cr.set_source_rgba(1.0, 1.0, 1.0, alpha);
+        cr.move_to(w * 0.08, cy - layout.pixel_size().1 as f64 / 2.0);
+        pangocairo::functions::show_layout(cr, &layout);
+    }
+
+    /// Three dots that fill one after another over the gap, breathing gently,
+    /// with the seconds left underneath. They shrink away just before the line starts.
+    fn draw_karaoke_countdown(cr: &cairo::Context, w: f64, cy: f64, radius: f64, progress: f64, remaining: f64) {
+        let cx = w / 2.0;
+        let pulse = 1.0 + 0.12 * (remaining * std::f64::consts::TAU * 0.8).sin();
+        let exit = ((1.0 - progress) / 0.08).min(1.0);
+        let r = radius * pulse * exit;
+        for k in 0..3 {
+            let fill = (progress * 3.0 - k as f64).clamp(0.0, 1.0);
+            cr.set_source_rgba(1.0, 1.0, 1.0, 0.25 + 0.75 * fill);
+            cr.arc(cx + (k as f64 - 1.0) * radius * 3.2, cy, r, 0.0, std::f64::consts::TAU);
+            cr.fill().unwrap();
+        }
+        if remaining >= 1.0 && exit >= 1.0 {
+            Self::draw_karaoke_text(cr, &format!("{}", remaining.ceil() as u64), w, cy + radius * 4.0, radius * 3.0, 0.45, false);
+        }
+    }
+
+    /// Load synced lyrics for a song from ~/Music/Lyrics/ (LRC, TTML, VTT or SRT),
+    /// applying any saved per-song offset override and attaching a
+    /// `{Artist} - {Title}.{lang}.{ext}` translation track if present.
+    fn load_lyrics(artist: &str, title: &str) -> Option<LRCParser> {
+        let mut lrc = lyrics::find_lyrics_file(artist, title).and_then(lyrics::load_lyrics_file)?;
+        if let Some(offset_ms) = lyrics::load_offset(artist, title) {
+            lrc.offset_ms = offset_ms;
+        }
+        if let Some(secondary) = lyrics::translation_path(artist, title).and_then(lyrics::load_lyrics_file) {
+            lrc.attach_translation(&secondary);
+        }
+        Some(lrc)
+    }
+
+    /// Rebuild the lyrics pane for `lrc`, or hide it when there are no lyrics.
+    fn show_lyrics(
+        lyrics_box: &GtkBox,
+        lyrics_scroll: &ScrolledWindow,
+        lrc: Option<&LRCParser>,
+        display: LyricsDisplay,
+        on_click: &Rc<dyn Fn(usize)>,
+    ) {
+        match lrc {
+            Some(lrc) => {
+                Self::populate_lyrics_box(lyrics_box, &lrc.lines, display, on_click);
+                lyrics_scroll.show();
+            }
+            None => {
+                Self::populate_lyrics_box(lyrics_box, &[], display, on_click);
+                lyrics_scroll.hide();
+            }
+        }
+    }
+
+    /// Replace the contents of `lyrics_box` with one clickable label per line,
+    /// the first highlighted until playback picks the current one and the rest
+    /// dimmed. Each label sits in an EventBox that carries the style classes
+    /// and calls `on_click` with the line index.
+    fn populate_lyrics_box(lyrics_box: &GtkBox, lines: &[LyricLine], display: LyricsDisplay, on_click: &Rc<dyn Fn(usize)>) {
+        for child in lyrics_box.children() {
+            lyrics_box.remove(&child);
+        }
+        for (i, line) in lines.iter().enumerate() {
+            let label = Label::new(None);
+            label.set_markup(&Self::lyric_markup(line, i == 0, display));
+            label.set_line_wrap(true);
+            label.set_line_wrap_mode(gtk::pango::WrapMode::WordChar);
+            label.set_justify(gtk::Justification::Center);
+            label.set_halign(Align::Center);
+            label.set_margin_top(4);
+            label.set_margin_bottom(4);
+
+            let line_box = gtk::EventBox::new();
+            line_box.set_visible_window(false);
+            line_box.set_halign(Align::Center);
+            line_box.add_events(gdk::EventMask::ENTER_NOTIFY_MASK | gdk::EventMask::LEAVE_NOTIFY_MASK);
+            line_box.style_context().add_class("lyrics-line");
+            line_box.style_context().add_class(if i == 0 { "lyrics-active" } else { "lyrics-dim" });
+            line_box.add(&label);
+
+            // Hover feedback: highlight the line and show a pointer cursor
+            line_box.connect_enter_notify_event(|eb, _| {
+                eb.style_context().add_class("lyrics-hover");
+                if let Some(win) = eb.window() {
+                    win.set_cursor(gdk::Cursor::from_name(&win.display(), "pointer").as_ref());
+                }
+                glib::Propagation::Proceed
+            });
+            line_box.connect_leave_notify_event(|eb, _| {
+                eb.style_context().remove_class("lyrics-hover");
+                if let Some(win) = eb.window() {
+                    win.set_cursor(None);
+                }
+                glib::Propagation::Proceed
+            });
+            let on_click = on_click.clone();
+            line_box.connect_button_release_event(move |_, event| {
+                if event.button() == 1 {
+                    on_click(i);
+                }
+                glib::Propagation::Proceed
+            });
+
+            lyrics_box.pack_start(&line_box, false, false, 0);
+        }
+        lyrics_box.show_all();
+    }
+
+    /// Click handler for lyric lines: seek MPD to the line's timestamp
+    /// (offset-corrected), highlight it and glide the view back onto it.
+    fn lyric_seek_handler(&self) -> Rc<dyn Fn(usize)> {
+        let mpd_clone = self.mpd.clone();
+        let current_lyrics = self.current_lyrics.clone();
+        let current_lyrics_index = self.current_lyrics_index.clone();
+        let lyrics_box = self.lyrics_box.clone();
+        let lyrics_scroll = self.lyrics_scroll.clone();
+        let lyrics_follow = self.lyrics_follow.clone();
+        let lyrics_resume_btn = self.lyrics_resume_btn.clone();
+        let lyrics_display = self.lyrics_display.clone();
+        let playback_clock = self.playback_clock.clone();
+        Rc::new(move |idx: usize| {
+            let lyrics_ref = current_lyrics.borrow();
+            // Unstamped lines in the sync editor have no timing yet
+            let Some(lrc) = lyrics_ref.as_ref() else { return };
+            let Some(line) = lrc.lines.get(idx) else { return };
+
+            // Land a hair past the stamp so the line is already current
+            let seek_to = (line.timestamp - lrc.offset_secs() + 0.01).max(0.0);
+            if let Ok(mut mpd) = mpd_clone.try_borrow_mut() {
+                let _ = mpd.seek(Duration::from_secs_f64(seek_to));
+            }
+            {
+                let mut clock = playback_clock.borrow_mut();
+                clock.elapsed = seek_to;
+                clock.sampled_at = Instant::now();
+            }
+
+            let mut last_idx = current_lyrics_index.borrow_mut();
+            let old_idx = last_idx.take().unwrap_or(0);
+            if let Some(old_line) = lrc.lines.get(old_idx) {
+                Self::set_lyric_active(&lyrics_box, old_idx, old_line, false, *lyrics_display.borrow());
+            }
+            if let Some(child) = Self::set_lyric_active(&lyrics_box, idx, line, true, *lyrics_display.borrow()) {
+                Self::resume_lyrics_follow(&lyrics_follow, &lyrics_resume_btn);
+                Self::scroll_lyrics_to(&lyrics_scroll, &child, &lyrics_follow);
+            }
+            *last_idx = Some(idx);
+        })
+    }
+
+    /// Pango markup for a lyric line; the active line is set in bold.
+    /// Depending on `display`, the translation replaces the original or sits
+    /// under it as a smaller second line.
+    fn lyric_markup(line: &LyricLine, active: bool, display: LyricsDisplay) -> String {
+        Self::karaoke_markup(line, active, display, None)
+    }
+
+    /// `lyric_markup` with karaoke progress: the first `sung` timed words are
+    /// drawn at full brightness and the rest faded.
+    fn karaoke_markup(line: &LyricLine, active: bool, display: LyricsDisplay, sung: Option<usize>) -> String {
+        let weight = if active { " weight='bold'" } else { "" };
+        let translation = line.translation.as_deref().filter(|t| !t.is_empty());
+        let showing_translation = display == LyricsDisplay::Translation && translation.is_some();
+        let primary = match translation {
+            Some(t) if showing_translation => t,
+            _ => line.text.as_str(),
+        };
+        if primary.is_empty() {
+            return "<span size='small'> </span>".to_string();
+        }
+        let body = match sung {
+            Some(sung) if !showing_translation && !line.words.is_empty() => line.words.iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    let alpha = if i < sung { "100%" } else { "55%" };
+                    format!("<span alpha='{}'>{}</span>", alpha, glib::markup_escape_text(&word.text))
+                })
+                .collect::<String>(),
+            _ => glib::markup_escape_text(primary).to_string(),
+        };
+        let mut markup = format!("<span size='medium'{}>{}</span>", weight, body);
+        if let (LyricsDisplay::Both, Some(t)) = (display, translation) {
+            markup.push_str(&format!("\n<span size='small' alpha='70%'>{}</span>", glib::markup_escape_text(t)));
+        }
+        markup
+    }
+
+    /// Switch line `idx` between the active and dimmed styles.
+    /// Returns the line's widget so the caller can scroll to it.
+    fn set_lyric_active(lyrics_box: &GtkBox, idx: usize, line: &LyricLine, active: bool, display: LyricsDisplay) -> Option<gtk::Widget> {
+        let child = lyrics_box.children().get(idx)?.clone();
+        let ctx = child.style_context();
+        if active {
+            ctx.remove_class("lyrics-dim");
+            ctx.add_class("lyrics-active");
+        } else {
+            ctx.remove_class("lyrics-active");
+            ctx.add_class("lyrics-dim");
+        }
+        if let Some(lbl) = Self::lyric_label(&child) {
+            lbl.set_markup(&Self::lyric_markup(line, active, display));
+        }
+        Some(child)
+    }
+
+    /// The Label inside a lyric line's EventBox.
+    fn lyric_label(child: &gtk::Widget) -> Option<Label> {
+        child.downcast_ref::<gtk::EventBox>()?.child()?.downcast::<Label>().ok()
+    }
+
+    /// Re-bin the waveform to as many bars as fit the area's current width
+    /// (no bars while nothing is loaded, which draws the placeholder).
+    fn rebin_waveform(
+        area: &DrawingArea,
+        style: &WaveformStyle,
+        pyramid: &Rc<RefCell<Option<PeakPyramid>>>,
+        peaks: &Rc<RefCell<Vec<PeakPair>>>,
+    ) {
+        let bars = style.bars_for_width(area.allocated_width());
+        *peaks.borrow_mut() = pyramid.borrow().as_ref().map(|p| p.bars(bars).peaks).unwrap_or_default();
+        area.queue_draw();
+    }
+
+    /// The popup behind the waveform's seek preview, with its label.
+    fn create_seek_preview() -> (gtk::Window, Label) {
+        let popup = gtk::Window::new(gtk::WindowType::Popup);
+        popup.set_type_hint(gdk::WindowTypeHint::Tooltip);
+        popup.style_context().add_class("seek-preview");
+        let label = Label::new(None);
+        label.set_justify(gtk::Justification::Center);
+        label.set_line_wrap(true);
+        label.set_max_width_chars(40);
+        popup.add(&label);
+        label.show();
+        (popup, label)
+    }
+
+    /// Float the seek preview above `area` at `x`: the time there, the
+    /// section boundary under the pointer if any and, with synced lyrics
+    /// loaded, the line sung at that time.
+    fn show_seek_preview(
+        popup: &gtk::Window,
+        label: &Label,
+        area: &DrawingArea,
+        x: f64,
+        duration: f64,
+        lrc: Option<&LRCParser>,
+        sections: Option<&TrackSections>,
+    ) {
+        let w = area.allocated_width() as f64;
+        let Some(window) = area.window().filter(|_| duration > 0.0 && w > 0.0) else {
+            popup.hide();
+            return;
+        };
+        let time = (x / w).clamp(0.0, 1.0) * duration;
+        let mut markup = format!("<b>{}</b>", format_time(time));
+        let marker = sections.and_then(|s| s.markers.iter().find(|m| (m.position * w - x).abs() <= 4.0));
+        if let Some(marker) = marker {
+            markup.push_str(&format!(" · {}", marker.kind.label()));
+        }
+        if let Some((_, text)) = lrc.and_then(|l| l.get_current_line(time)) {
+            if !text.trim().is_empty() {
+                markup.push_str(&format!("\n<small>{}</small>", glib::markup_escape_text(text)));
+            }
+        }
+        label.set_markup(&markup);
+
+        // Shrink-wrap to the new text, centred over the pointer just above the waveform
+        let (_, pw) = popup.preferred_width();
+        let (_, ph) = popup.preferred_height_for_width(pw);
+        popup.resize(pw, ph);
+        let (_, ox, oy) = window.origin();
+        popup.move_(ox + x.clamp(0.0, w) as i32 - pw / 2, oy - ph - 6);
+        popup.show();
+    }
+
+    /// Move the playhead by `delta` seconds. During a drag this shifts the
+    /// pending drop position; otherwise it seeks right away.
+    fn waveform_nudge_handler(&self, preview: &Rc<dyn Fn(f64)>, popup: &gtk::Window) -> Rc<dyn Fn(f64)> {
+        let mpd_clone = self.mpd.clone();
+        let area = self.waveform_area.clone();
+        let wf_pos = self.waveform_position.clone();
+        let wf_hover = self.waveform_hover.clone();
+        let is_seeking = self.is_seeking.clone();
+        let playback_clock = self.playback_clock.clone();
+        let preview = preview.clone();
+        let popup = popup.clone();
+        // Bumped per nudge so only the latest one's hide timer fires
+        let nudges = Rc::new(Cell::new(0u32));
+        Rc::new(move |delta: f64| {
+            let duration = playback_clock.borrow().duration;
+            if duration <= 0.0 {
+                return;
+            }
+            let pos = if *is_seeking.borrow() {
+                let pos = (*wf_pos.borrow() + delta / duration).clamp(0.0, 1.0);
+                *wf_pos.borrow_mut() = pos;
+                pos
+            } else {
+                let seek_to = (playback_clock.borrow().now() + delta).clamp(0.0, duration);
+                // MPD busy elsewhere: don't move the playhead to where it didn't seek
+                let Ok(mut mpd) = mpd_clone.try_borrow_mut() else { return };
+                if mpd.seek(Duration::from_secs_f64(seek_to)).is_err() {
+                    return;
+                }
+                drop(mpd);
+                let mut clock = playback_clock.borrow_mut();
+                clock.elapsed = seek_to;
+                clock.sampled_at = Instant::now();
+                *wf_pos.borrow_mut() = seek_to / duration;
+                seek_to / duration
+            };
+            preview(pos * area.allocated_width() as f64);
+            area.queue_draw();
+
+            // From the keyboard there's no pointer to leave the waveform; hide after a moment
+            nudges.set(nudges.get().wrapping_add(1));
+            let nudge_id = nudges.get();
+            let nudges = nudges.clone();
+            let wf_hover = wf_hover.clone();
+            let is_seeking = is_seeking.clone();
+            let popup = popup.clone();
+            glib::timeout_add_local_once(Duration::from_millis(1200), move || {
+                if nudges.get() == nudge_id && wf_hover.borrow().is_none() && !*is_seeking.borrow() {
+                    popup.hide();
+                }
+            });
+        })
+    }
+
+    /// Smoothly scroll the lyrics pane so `child` sits in the vertical centre.
+    fn scroll_lyrics_to(lyrics_scroll: &ScrolledWindow, child: &gtk::Widget, follow: &Rc<RefCell<LyricsFollow>>) {
+        let mut state = follow.borrow_mut();
+        // The user is reading elsewhere; don't yank the view away
+        if state.is_paused() {
+            return;
+        }
+        let alloc = child.allocation();
+        let scroll_h = lyrics_scroll.allocated_height() as f64;
+        let target_y = (alloc.y() as f64) - (scroll_h / 2.0) + (alloc.height() as f64 / 2.0);
+        state.target = Some(target_y.max(0.0));
+        // A running animation just picks up the new target
+        if state.anim.is_some() {
+            return;
+        }
+        let scroll_anim = lyrics_scroll.clone();
+        let follow_anim = follow.clone();
+        let id = glib::timeout_add_local(std::time::Duration::from_millis(16), move || {
+            let mut state = follow_anim.borrow_mut();
+            let adj = scroll_anim.vadjustment();
+            let target_val = match state.target {
+                Some(t) => t,
+                None => {
+                    state.anim = None;
+                    return glib::ControlFlow::Break;
+                }
+            };
+            let cur = adj.value();
+            let diff = target_val - cur;
+            if diff.abs() < 1.0 {
+                adj.set_value(target_val);
+                state.target = None;
+                state.anim = None;
+                return glib::ControlFlow::Break;
             }
+            // Ease toward target (lerp 15% per frame)
+            adj.set_value(cur + diff * 0.15);
             glib::ControlFlow::Continue
         });
+        state.anim = Some(id);
+    }
+
+    /// Manual scrolling on the lyrics pane: stop any running follow animation,
+    /// pause auto-follow for `LYRICS_FOLLOW_GRACE` and offer a way back.
+    fn connect_lyrics_follow(&self) {
+        let lyrics_follow = self.lyrics_follow.clone();
+        let lyrics_resume_btn = self.lyrics_resume_btn.clone();
+        self.lyrics_scroll.connect_scroll_event(move |_, _| {
+            let mut state = lyrics_follow.borrow_mut();
+            state.cancel_animation();
+            state.paused_until = Some(Instant::now() + LYRICS_FOLLOW_GRACE);
+            lyrics_resume_btn.show();
+            glib::Propagation::Proceed
+        });
+
+        let lyrics_follow = self.lyrics_follow.clone();
+        let lyrics_box = self.lyrics_box.clone();
+        let lyrics_scroll = self.lyrics_scroll.clone();
+        let current_lyrics_index = self.current_lyrics_index.clone();
+        self.lyrics_resume_btn.connect_clicked(move |btn| {
+            Self::resume_lyrics_follow(&lyrics_follow, btn);
+            if let Some(idx) = *current_lyrics_index.borrow() {
+                if let Some(child) = lyrics_box.children().get(idx) {
+                    Self::scroll_lyrics_to(&lyrics_scroll, child, &lyrics_follow);
+                }
+            }
+        });
+    }
+
+    /// The loudness panel (`i`): EBU R128 values for the current track and its
+    /// album — the MP3/FLAC songs MPD lists with the same album tags — plus a
+    /// button that writes them to every file as ReplayGain tags, and one that
+    /// tags the current track with its tempo and key.
+    fn connect_track_info(&self) {
+        let popover = self.track_info.clone();
+        popover.style_context().add_class("track-info");
+
+        let vbox = GtkBox::new(Orientation::Vertical, 8);
+        vbox.set_margin_top(10);
+        vbox.set_margin_bottom(10);
+        vbox.set_margin_start(12);
+        vbox.set_margin_end(12);
+
+        let grid = gtk::Grid::new();
+        grid.set_row_spacing(4);
+        grid.set_column_spacing(16);
+        for (col, heading) in ["", "Track", "Album"].into_iter().enumerate() {
+            let label = Label::new(Some(heading));
+            label.set_xalign(0.0);
+            label.style_context().add_class("track-info-heading");
+            grid.attach(&label, col as i32, 0, 1, 1);
+        }
+        let mut track_values = Vec::new();
+        let mut album_values = Vec::new();
+        for (row, name) in ["Integrated", "Loudness range", "True peak", "ReplayGain"].into_iter().enumerate() {
+            let label = Label::new(Some(name));
+            label.set_xalign(0.0);
+            label.style_context().add_class("track-info-name");
+            grid.attach(&label, 0, row as i32 + 1, 1, 1);
+            for (col, values) in [&mut track_values, &mut album_values].into_iter().enumerate() {
+                let value = Label::new(None);
+                value.set_xalign(1.0);
+                grid.attach(&value, col as i32 + 1, row as i32 + 1, 1, 1);
+                values.push(value);
+            }
+        }
+        vbox.pack_start(&grid, false, false, 0);
+
+        let status = Label::new(None);
+        status.set_xalign(0.0);
+        status.set_line_wrap(true);
+        status.set_max_width_chars(36);
+        status.style_context().add_class("track-info-status");
+        vbox.pack_start(&status, false, false, 0);
+
+        let write_btn = Button::with_label("Write ReplayGain tags");
+        write_btn.set_sensitive(false);
+        vbox.pack_start(&write_btn, false, false, 0);
+
+        let tempo_btn = Button::with_label("Write BPM and key tags");
+        vbox.pack_start(&tempo_btn, false, false, 0);
+
+        vbox.show_all();
+        popover.add(&vbox);
+
+        // Cancellation token of the running scan, and what it measured
+        let scan_cancel: Rc<RefCell<Option<Arc<AtomicBool>>>> = Rc::new(RefCell::new(None));
+        let album: Rc<RefCell<Vec<(PathBuf, TrackLoudness)>>> = Rc::new(RefCell::new(Vec::new()));
+
+        {
+            let scan_cancel = scan_cancel.clone();
+            let album = album.clone();
+            let status = status.clone();
+            let write_btn = write_btn.clone();
+            let tempo_btn = tempo_btn.clone();
+            let track_analysis = self.track_analysis.clone();
+            let current_song_file = self.current_song_file.clone();
+            let cache = self.waveform_cache.clone();
+            let mpd = self.mpd.clone();
+            popover.connect_show(move |popover| {
+                let file = current_song_file.borrow().clone();
+                let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+                let music_dir = PathBuf::from(&home).join("Music");
+                let full_path = music_dir.join(&file);
+
+                let cancel = Arc::new(AtomicBool::new(false));
+                if let Some(old) = scan_cancel.borrow_mut().replace(cancel.clone()) {
+                    old.store(true, Ordering::Relaxed);
+                }
+                album.borrow_mut().clear();
+                Self::show_loudness(&track_values, None);
+                Self::show_loudness(&album_values, None);
+                write_btn.set_sensitive(false);
+                tempo_btn.set_sensitive(track_analysis.borrow().as_ref().is_some_and(|a| a.describe().is_some()));
+                status.set_text("Measuring track…");
+
+                // The album comes from MPD's tags, not the folder: a flat library
+                // would otherwise make every file in it one "album"
+                let album_songs = mpd.try_borrow_mut().ok().and_then(|mut mpd| {
+                    let song = mpd.current_song().ok().flatten().filter(|song| song.file == file)?;
+                    mpd.album_songs(&song)
+                        .map_err(|e| log::warn!("Failed to look up the album in MPD: {}", e))
+                        .ok()
+                });
+                let mut files: Vec<PathBuf> = match album_songs {
+                    Some(songs) => songs.iter().map(|song| music_dir.join(&song.file)).collect(),
+                    None => vec![full_path.clone()],
+                };
+                files.retain(|p| loudness::can_write_tags(p));
+                files.sort();
+                files.dedup();
+
+                let (tx, rx) = std::sync::mpsc::channel();
+                let cache = cache.clone();
+                std::thread::spawn(move || {
+                    let track = Self::measure_loudness(&cache, &music_dir, &full_path, &cancel);
+                    let _ = tx.send(LoudnessUpdate::Track(track.as_ref().and_then(TrackLoudness::summary)));
+
+                    let total = files.len();
+                    let mut measured = Vec::new();
+                    for (i, path) in files.into_iter().enumerate() {
+                        if cancel.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        let _ = tx.send(LoudnessUpdate::Progress(i, total));
+                        let loudness = if path == full_path {
+                            track.clone()
+                        } else {
+                            Self::measure_loudness(&cache, &music_dir, &path, &cancel)
+                        };
+                        if let Some(loudness) = loudness {
+                            measured.push((path, loudness));
+                        }
+                    }
+                    cache.evict();
+                    if !cancel.load(Ordering::Relaxed) {
+                        let _ = tx.send(LoudnessUpdate::Album(measured, total));
+                    }
+                });
+
+                let popover = popover.clone();
+                let album = album.clone();
+                let status = status.clone();
+                let write_btn = write_btn.clone();
+                let track_values = track_values.clone();
+                let album_values = album_values.clone();
+                let current_song_file = current_song_file.clone();
+                let cancel = scan_cancel.borrow().clone();
+                // Runs while this panel is open (closing or reopening trips the token)
+                glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
+                    if cancel.as_ref().is_none_or(|c| c.load(Ordering::Relaxed)) {
+                        return glib::ControlFlow::Break;
+                    }
+                    // The panel describes one song; close it when that changes
+                    if *current_song_file.borrow() != file {
+                        popover.popdown();
+                        return glib::ControlFlow::Break;
+                    }
+                    loop {
+                        match rx.try_recv() {
+                            Ok(LoudnessUpdate::Track(summary)) => {
+                                Self::show_loudness(&track_values, summary.as_ref());
+                                if summary.is_none() {
+                                    status.set_text("This track can't be measured (only natively decoded formats are)");
+                                }
+                            }
+                            Ok(LoudnessUpdate::Progress(done, total)) => {
+                                status.set_text(&format!("Measuring album… {}/{}", done + 1, total));
+                            }
+                            Ok(LoudnessUpdate::Album(measured, total)) => {
+                                let tracks: Vec<TrackLoudness> = measured.iter().map(|(_, l)| l.clone()).collect();
+                                Self::show_loudness(&album_values, loudness::album_summary(&tracks).as_ref());
+                                status.set_text(&if measured.len() == total {
+                                    format!("Album: {} files", total)
+                                } else {
+                                    format!("Album: {} of {} files measured (others can't be decoded natively)", measured.len(), total)
+                                });
+                                write_btn.set_sensitive(!measured.is_empty());
+                                *album.borrow_mut() = measured;
+                            }
+                            Err(_) => return glib::ControlFlow::Continue,
+                        }
+                    }
+                });
+            });
+        }
+
+        {
+            let scan_cancel = scan_cancel.clone();
+            popover.connect_closed(move |_| {
+                if let Some(cancel) = scan_cancel.borrow_mut().take() {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+
+        {
+            let status = status.clone();
+            let mpd = self.mpd.clone();
+            let cache = self.waveform_cache.clone();
+            let track_analysis = self.track_analysis.clone();
+            let current_song_file = self.current_song_file.clone();
+            tempo_btn.connect_clicked(move |_| {
+                let Some(analysis) = *track_analysis.borrow() else { return };
+                let file = current_song_file.borrow().clone();
+                let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+                let path = PathBuf::from(&home).join("Music").join(&file);
+                let old_key = CacheKey::new(&file, &path);
+                match track_analysis::write_tags(&path, &analysis) {
+                    Ok(()) => {
+                        if let (Some(old_key), Some(new_key)) = (old_key, CacheKey::new(&file, &path)) {
+                            cache.carry_over(&old_key, &new_key);
+                        }
+                        status.set_text("Wrote BPM and key tags");
+                        // Let MPD pick up the new tags
+                        if let Err(e) = mpd.borrow_mut().update() {
+                            log::warn!("Failed to start MPD database update: {}", e);
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to write BPM and key tags to {}: {}", path.display(), e);
+                        status.set_text(&format!("Could not write tags: {}", e));
+                    }
+                }
+            });
+        }
+
+        let mpd = self.mpd.clone();
+        let cache = self.waveform_cache.clone();
+        write_btn.connect_clicked(move |btn| {
+            let measured = album.borrow().clone();
+            let tracks: Vec<TrackLoudness> = measured.iter().map(|(_, l)| l.clone()).collect();
+            let album_summary = loudness::album_summary(&tracks);
+            btn.set_sensitive(false);
+            status.set_text("Writing tags…");
+
+            let (tx, rx) = std::sync::mpsc::channel();
+            let cache = cache.clone();
+            std::thread::spawn(move || {
+                let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+                let music_dir = PathBuf::from(&home).join("Music");
+                let total = measured.len();
+                let mut written = 0;
+                for (path, loudness) in measured {
+                    let Some(summary) = loudness.summary() else { continue };
+                    let rel = path.strip_prefix(&music_dir).unwrap_or(&path).to_string_lossy().to_string();
+                    let old_key = CacheKey::new(&rel, &path);
+                    if let Err(e) = loudness::write_replaygain(&path, &summary, album_summary.as_ref()) {
+                        log::warn!("Failed to write ReplayGain tags to {}: {}", path.display(), e);
+                        continue;
+                    }
+                    written += 1;
+                    // Only the tags changed: keep the cached analyses rather than decoding again
+                    if let (Some(old_key), Some(new_key)) = (old_key, CacheKey::new(&rel, &path)) {
+                        cache.carry_over(&old_key, &new_key);
+                    }
+                }
+                let _ = tx.send((written, total));
+            });
+
+            let btn = btn.clone();
+            let status = status.clone();
+            let mpd = mpd.clone();
+            glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
+                match rx.try_recv() {
+                    Ok((written, total)) => {
+                        status.set_text(&if written == total {
+                            format!("Tagged {} files", written)
+                        } else {
+                            format!("Tagged {} of {} files (see the log for the rest)", written, total)
+                        });
+                        btn.set_sensitive(true);
+                        // Let MPD pick up the new tags
+                        if written > 0 {
+                            if let Err(e) = mpd.borrow_mut().update() {
+                                log::warn!("Failed to start MPD database update: {}", e);
+                            }
+                        }
+                        glib::ControlFlow::Break
+                    }
+                    Err(std::sync::mpsc::TryRecvError::Empty) => glib::ControlFlow::Continue,
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => glib::ControlFlow::Break,
+                }
+            });
+        });
+    }
+
+    /// Show the current song's tempo and key under its album (hidden while
+    /// unknown) and keep them for the tag writer.
+    fn show_track_analysis(label: &Label, current: &Rc<RefCell<Option<TrackAnalysis>>>, analysis: Option<TrackAnalysis>) {
+        match analysis.as_ref().and_then(TrackAnalysis::describe) {
+            Some(text) => {
+                label.set_text(&text);
+                label.show();
+            }
+            None => label.hide(),
+        }
+        *current.borrow_mut() = analysis;
+    }
+
+    /// Fill a column of the loudness panel; dashes while unknown.
+    fn show_loudness(values: &[Label], summary: Option<&LoudnessSummary>) {
+        let texts = match summary {
+            Some(s) => [
+                format!("{:.1} LUFS", s.integrated),
+                format!("{:.1} LU", s.range),
+                format!("{:.1} dBTP", s.true_peak_db()),
+                format!("{:+.2} dB", s.replaygain()),
+            ],
+            None => Default::default(),
+        };
+        for (label, text) in values.iter().zip(texts) {
+            label.set_text(if text.is_empty() { "—" } else { &text });
+        }
+    }
+
+    /// Loudness of `path`, from the analysis cache when the waveform decode
+    /// (or an earlier scan) already measured it, otherwise decoded here and
+    /// cached. Runs on a background thread.
+    fn measure_loudness(cache: &WaveformCache, music_dir: &Path, path: &Path, cancel: &AtomicBool) -> Option<TrackLoudness> {
+        let rel = path.strip_prefix(music_dir).unwrap_or(path).to_string_lossy().to_string();
+        let key = CacheKey::new(&rel, path);
+        if let Some(loudness) = key.as_ref().and_then(|k| cache.load_loudness(k)) {
+            return Some(loudness);
+        }
+        let loudness = loudness::analyze_file(path, cancel)?;
+        if let Some(key) = key {
+            if let Err(e) = cache.store_loudness(&key, &loudness) {
+                log::warn!("Failed to cache loudness: {}", e);
+            }
+        }
+        Some(loudness)
+    }
+
+    /// End a manual-scroll pause so the next `scroll_lyrics_to` follows again.
+    fn resume_lyrics_follow(follow: &Rc<RefCell<LyricsFollow>>, resume_btn: &Button) {
+        follow.borrow_mut().paused_until = None;
+        resume_btn.hide();
+    }
+
+    /// Show a short message in the lyrics status line and hide it after a few seconds.
+    fn flash_lyrics_status(status: &Label, message: &str) {
+        status.set_text(message);
+        status.show();
+        let status_c = status.clone();
+        let message = message.to_string();
+        glib::timeout_add_local_once(std::time::Duration::from_secs(3), move || {
+            // Only hide if nothing newer has been shown since
+            if status_c.text() == message {
+                status_c.hide();
+            }
+        });
     }
 
     /// Returns the cache directory path: ~/.cache/ArcanistPlayer/
@@ -1662,6 +3937,29 @@ impl MusicPlayerWindow {
     }
 }
 
+/// Fill `w`×`h` with a four-corner mesh gradient.
+/// `pal` is [top-left, top-right, bottom-left, bottom-right] RGB tuples.
+fn paint_palette_gradient(cr: &cairo::Context, w: f64, h: f64, pal: &[(f64, f64, f64); 4]) {
+    let (tl_r, tl_g, tl_b) = pal[0];
+    let (tr_r, tr_g, tr_b) = pal[1];
+    let (bl_r, bl_g, bl_b) = pal[2];
+    let (br_r, br_g, br_b) = pal[3];
+
+    let mesh = cairo::Mesh::new();
+    mesh.begin_patch();
+    mesh.move_to(0.0, 0.0);
+    mesh.line_to(w, 0.0);
+    mesh.line_to(w, h);
+    mesh.line_to(0.0, h);
+    mesh.set_corner_color_rgb(cairo::MeshCorner::MeshCorner0, tl_r, tl_g, tl_b);
+    mesh.set_corner_color_rgb(cairo::MeshCorner::MeshCorner1, tr_r, tr_g, tr_b);
+    mesh.set_corner_color_rgb(cairo::MeshCorner::MeshCorner2, br_r, br_g, br_b);
+    mesh.set_corner_color_rgb(cairo::MeshCorner::MeshCorner3, bl_r, bl_g, bl_b);
+    mesh.end_patch();
+    cr.set_source(&mesh).unwrap();
+    cr.paint().unwrap();
+}
+
 /// Apply multi-pass box blur to a Cairo ImageSurface, returning a new blurred surface.
 fn blur_surface(surf: &mut cairo::ImageSurface, radius: i32, passes: u32) -> Option<cairo::ImageSurface> {
     let w = surf.width();
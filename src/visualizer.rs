@@ -0,0 +1,635 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, ErrorKind, Read};
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+use crate::cava::CavaVisualizer;
+use crate::config::Config;
+
+/// Where MPD's `fifo` output usually writes, and its usual format.
+const DEFAULT_FIFO: &str = "/tmp/mpd.fifo";
+const DEFAULT_FORMAT: &str = "44100:16:2";
+
+/// Delay before reopening the FIFO after a read error; it doubles with each
+/// failed attempt up to the maximum.
+const MIN_REOPEN_DELAY: Duration = Duration::from_secs(1);
+const MAX_REOPEN_DELAY: Duration = Duration::from_secs(60);
+
+/// Samples per FFT; long enough to separate bass notes.
+const FFT_SIZE: usize = 4096;
+
+/// Bar updates per second.
+const FRAME_RATE: u32 = 60;
+
+/// Bars are spaced logarithmically over this range, in Hz.
+const MIN_FREQ: f64 = 50.0;
+const MAX_FREQ: f64 = 10000.0;
+
+/// Levels this far below the reference (dB) draw as empty bars. The reference
+/// follows the loudest recent band, falling `REFERENCE_FALL_DB` per frame, so
+/// quiet and loud tracks both fill the bars.
+const RANGE_DB: f64 = 45.0;
+const REFERENCE_FALL_DB: f64 = 0.05;
+const MIN_REFERENCE_DB: f64 = -50.0;
+
+/// Share of the gap to a higher level closed each frame, and how fast falling
+/// bars accelerate (bar heights per frame²).
+const ATTACK: f64 = 0.6;
+const GRAVITY: f64 = 0.004;
+
+/// Height of a full bar in the shared buffer.
+pub const BAR_MAX: u16 = u16::MAX;
+
+/// Bar count limits; stereo counts are rounded up to even.
+const MIN_BARS: usize = 2;
+const MAX_BARS: usize = 256;
+const DEFAULT_BARS: usize = 24;
+
+/// Where the bars are drawn relative to the album art.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VisualizerLayout {
+    /// Stacked beside the art, growing away from it
+    Vertical,
+    /// In a row under the art, growing upwards
+    Horizontal,
+    /// Around the art, growing outwards
+    Radial,
+}
+
+impl VisualizerLayout {
+    pub fn from_config(config: &Config) -> Self {
+        match config.get("visualizer", "orientation") {
+            Some(layout) if layout.eq_ignore_ascii_case("horizontal") => VisualizerLayout::Horizontal,
+            Some(layout) if layout.eq_ignore_ascii_case("radial") => VisualizerLayout::Radial,
+            _ => VisualizerLayout::Vertical,
+        }
+    }
+
+    /// The next layout when cycling through them at runtime.
+    pub fn next(self) -> Self {
+        match self {
+            VisualizerLayout::Vertical => VisualizerLayout::Horizontal,
+            VisualizerLayout::Horizontal => VisualizerLayout::Radial,
+            VisualizerLayout::Radial => VisualizerLayout::Vertical,
+        }
+    }
+}
+
+/// `[visualizer]` settings shared by both backends:
+///
+/// ```ini
+/// [visualizer]
+/// bars = 24
+/// channels = stereo       # or mono (default)
+/// bit_format = 16bit      # or 8bit (default); CAVA's output precision
+/// orientation = radial    # vertical (default), horizontal or radial
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct VisualizerSettings {
+    pub bars: usize,
+    /// Left and right channels in mirrored halves, bass in the middle
+    pub stereo: bool,
+    /// 16-bit rather than 8-bit bar heights from CAVA
+    pub high_precision: bool,
+    pub layout: VisualizerLayout,
+}
+
+impl VisualizerSettings {
+    pub fn from_config(config: &Config) -> Self {
+        let stereo = config.get("visualizer", "channels").is_some_and(|c| c.eq_ignore_ascii_case("stereo"));
+        let bars = config
+            .get("visualizer", "bars")
+            .and_then(|b| b.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_BARS)
+            .clamp(MIN_BARS, MAX_BARS);
+        Self {
+            bars: if stereo { bars.next_multiple_of(2) } else { bars },
+            stereo,
+            high_precision: config.get("visualizer", "bit_format").is_some_and(|b| b.eq_ignore_ascii_case("16bit")),
+            layout: VisualizerLayout::from_config(config),
+        }
+    }
+}
+
+/// A running visualiser backend; both write bar heights (0–`BAR_MAX`) into
+/// the shared buffer that the draw callback reads.
+pub enum Visualizer {
+    Native(FifoVisualizer),
+    Cava(CavaVisualizer),
+}
+
+impl Visualizer {
+    /// The native analyser if the configured MPD FIFO exists, else CAVA;
+    /// None if neither is available.
+    pub fn start(config: &Config, settings: VisualizerSettings) -> Option<Self> {
+        match FifoVisualizer::from_config(config, settings) {
+            Some(native) => Some(Visualizer::Native(native)),
+            None => CavaVisualizer::new(settings).map(Visualizer::Cava),
+        }
+    }
+
+    pub fn get_bars_arc(&self) -> Arc<Mutex<Vec<u16>>> {
+        match self {
+            Visualizer::Native(native) => native.get_bars_arc(),
+            Visualizer::Cava(cava) => cava.get_bars_arc(),
+        }
+    }
+
+    /// Stop analysing while nothing would be shown; the bars drop to zero.
+    pub fn set_paused(&self, paused: bool) {
+        match self {
+            Visualizer::Native(native) => native.set_paused(paused),
+            Visualizer::Cava(cava) => cava.set_paused(paused),
+        }
+    }
+}
+
+/// Sample format of MPD's FIFO output, as in its `format "44100:16:2"` line.
+#[derive(Clone, Copy, Debug)]
+pub struct FifoFormat {
+    pub sample_rate: u32,
+    /// 16 or 32 (signed, native byte order)
+    pub bits: u16,
+    pub channels: usize,
+}
+
+impl FifoFormat {
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.trim().split(':');
+        let sample_rate = parts.next()?.trim().parse().ok()?;
+        let bits = parts.next()?.trim().parse().ok()?;
+        let channels = parts.next()?.trim().parse().ok()?;
+        let valid = sample_rate > 0 && matches!(bits, 16 | 32) && (1..=8).contains(&channels);
+        (valid && parts.next().is_none()).then_some(Self { sample_rate, bits, channels })
+    }
+
+    fn bytes_per_frame(&self) -> usize {
+        self.bits as usize / 8 * self.channels
+    }
+
+    /// One channel's sample from a frame of raw bytes, in -1.0–1.0.
+    fn sample(&self, frame: &[u8], channel: usize) -> f32 {
+        match self.bits {
+            16 => {
+                let b = &frame[channel * 2..];
+                i16::from_ne_bytes([b[0], b[1]]) as f32 / 32768.0
+            }
+            _ => {
+                let b = &frame[channel * 4..];
+                i32::from_ne_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2147483648.0
+            }
+        }
+    }
+
+    /// Mix a frame down to a mono sample.
+    fn mono(&self, frame: &[u8]) -> f32 {
+        (0..self.channels).map(|c| self.sample(frame, c)).sum::<f32>() / self.channels as f32
+    }
+}
+
+/// Spectrum analyser reading PCM from an MPD `fifo` output, so the bars work
+/// without CAVA. Its bar heights always have 16-bit precision. Configured
+/// under `[visualizer]`:
+///
+/// ```ini
+/// [visualizer]
+/// fifo = /tmp/mpd.fifo
+/// fifo_format = 44100:16:2
+/// ```
+pub struct FifoVisualizer {
+    bars: Arc<Mutex<Vec<u16>>>,
+    paused: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+}
+
+impl FifoVisualizer {
+    /// Start reading the configured FIFO; None if it doesn't exist, isn't a
+    /// FIFO, can't be opened, or its format isn't understood.
+    pub fn from_config(config: &Config, settings: VisualizerSettings) -> Option<Self> {
+        let path = PathBuf::from(config.get("visualizer", "fifo").unwrap_or(DEFAULT_FIFO));
+        let format_text = config.get("visualizer", "fifo_format").unwrap_or(DEFAULT_FORMAT);
+        let Some(format) = FifoFormat::parse(format_text) else {
+            log::warn!("Unsupported visualizer fifo_format {:?}; expected e.g. {}", format_text, DEFAULT_FORMAT);
+            return None;
+        };
+        Self::new(&path, format, settings)
+    }
+
+    pub fn new(path: &Path, format: FifoFormat, settings: VisualizerSettings) -> Option<Self> {
+        if settings.bars == 0 || !std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo()) {
+            return None;
+        }
+        let fifo = open_fifo(path)
+            .map_err(|e| log::warn!("Can't open visualizer FIFO {}: {}", path.display(), e))
+            .ok()?;
+
+        let visualizer = Self {
+            bars: Arc::new(Mutex::new(vec![0; settings.bars])),
+            paused: Arc::new(AtomicBool::new(false)),
+            stop: Arc::new(AtomicBool::new(false)),
+        };
+        let (bars, paused, stop) = (visualizer.bars.clone(), visualizer.paused.clone(), visualizer.stop.clone());
+        let path = path.to_path_buf();
+        thread::spawn(move || read_fifo(fifo, &path, format, settings, &bars, &paused, &stop));
+
+        Some(visualizer)
+    }
+
+    /// While paused the FIFO isn't read (MPD discards what doesn't fit) and
+    /// the bars are zero.
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Get a clone of the Arc holding bar data, for sharing with draw callbacks.
+    pub fn get_bars_arc(&self) -> Arc<Mutex<Vec<u16>>> {
+        Arc::clone(&self.bars)
+    }
+}
+
+impl Drop for FifoVisualizer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Non-blocking, so opening doesn't wait for MPD and reads don't hang while
+/// it's paused.
+fn open_fifo(path: &Path) -> io::Result<File> {
+    OpenOptions::new().read(true).custom_flags(libc::O_NONBLOCK).open(path)
+}
+
+/// Reopen the FIFO after a read error, backing off from `MIN_REOPEN_DELAY`;
+/// None if the visualizer is dropped first.
+fn reopen_fifo(path: &Path, stop: &AtomicBool) -> Option<File> {
+    let mut delay = MIN_REOPEN_DELAY;
+    loop {
+        let deadline = Instant::now() + delay;
+        while Instant::now() < deadline {
+            if stop.load(Ordering::Relaxed) {
+                return None;
+            }
+            thread::sleep(Duration::from_millis(100).min(deadline.saturating_duration_since(Instant::now())));
+        }
+        let is_fifo = std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo());
+        match open_fifo(path) {
+            Ok(fifo) if is_fifo => return Some(fifo),
+            _ => delay = (delay * 2).min(MAX_REOPEN_DELAY),
+        }
+    }
+}
+
+fn read_fifo(
+    mut fifo: File,
+    path: &Path,
+    format: FifoFormat,
+    settings: VisualizerSettings,
+    bars: &Mutex<Vec<u16>>,
+    paused: &AtomicBool,
+    stop: &AtomicBool,
+) {
+    // In stereo each channel gets half the bars, the left one mirrored so
+    // the bass of both meets in the middle (as CAVA does)
+    let stereo = settings.stereo && format.channels >= 2;
+    let channels = if stereo { 2 } else { 1 };
+    let mut analyzers: Vec<ChannelAnalyzer> =
+        (0..channels).map(|_| ChannelAnalyzer::new(format.sample_rate, settings.bars / channels)).collect();
+    let mut smoother = BarSmoother::new(settings.bars);
+    let frame_bytes = format.bytes_per_frame();
+    let mut buf = vec![0u8; frame_bytes * 1024];
+    // Bytes of a frame split across reads
+    let mut partial: Vec<u8> = Vec::with_capacity(frame_bytes);
+    let idle = Duration::from_secs(1) / FRAME_RATE;
+
+    let push = |frame: &[u8], analyzers: &mut [ChannelAnalyzer], smoother: &mut BarSmoother| {
+        let ready = if stereo {
+            let left = analyzers[0].push(format.sample(frame, 0));
+            analyzers[1].push(format.sample(frame, 1)) && left
+        } else {
+            analyzers[0].push(format.mono(frame))
+        };
+        if ready {
+            let mut levels = analyzers[0].band_levels_db();
+            if stereo {
+                levels.reverse();
+                levels.extend(analyzers[1].band_levels_db());
+            }
+            smoother.update(&levels);
+            smoother.publish(bars);
+        }
+    };
+
+    while !stop.load(Ordering::Relaxed) {
+        if paused.load(Ordering::Relaxed) {
+            analyzers.iter_mut().for_each(ChannelAnalyzer::reset);
+            if smoother.reset() {
+                smoother.publish(bars);
+            }
+            thread::sleep(idle);
+            continue;
+        }
+        match fifo.read(&mut buf) {
+            Ok(n) if n > 0 => {
+                let mut data = &buf[..n];
+                if !partial.is_empty() {
+                    let take = (frame_bytes - partial.len()).min(data.len());
+                    partial.extend_from_slice(&data[..take]);
+                    data = &data[take..];
+                    if partial.len() < frame_bytes {
+                        continue;
+                    }
+                    push(&partial, &mut analyzers, &mut smoother);
+                    partial.clear();
+                }
+                let mut frames = data.chunks_exact(frame_bytes);
+                for frame in &mut frames {
+                    push(frame, &mut analyzers, &mut smoother);
+                }
+                partial.extend_from_slice(frames.remainder());
+            }
+            // Nothing playing (or MPD not writing): let the bars fall
+            Ok(_) => {
+                if smoother.decay() {
+                    smoother.publish(bars);
+                }
+                thread::sleep(idle);
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::Interrupted => {
+                if smoother.decay() {
+                    smoother.publish(bars);
+                }
+                thread::sleep(idle);
+            }
+            // The bars drop to zero until the FIFO can be read again
+            Err(e) => {
+                log::warn!("Visualizer FIFO read failed: {}; reopening {}", e, path.display());
+                analyzers.iter_mut().for_each(ChannelAnalyzer::reset);
+                partial.clear();
+                if smoother.reset() {
+                    smoother.publish(bars);
+                }
+                match reopen_fifo(path, stop) {
+                    Some(reopened) => fifo = reopened,
+                    None => break,
+                }
+            }
+        }
+    }
+}
+
+/// FFT of one channel's latest samples every 1/`FRAME_RATE` s, binned into
+/// log-spaced bands.
+struct ChannelAnalyzer {
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    /// FFT bin range of each bar
+    bands: Vec<(usize, usize)>,
+    /// The last `FFT_SIZE` samples, oldest first once `filled`
+    samples: Vec<f32>,
+    next: usize,
+    filled: bool,
+    /// Samples between frames, and received since the last one
+    hop: usize,
+    pending: usize,
+    spectrum: Vec<Complex<f32>>,
+}
+
+impl ChannelAnalyzer {
+    fn new(sample_rate: u32, num_bars: usize) -> Self {
+        let nyquist_bin = FFT_SIZE / 2;
+        let bin_of = |freq: f64| ((freq * FFT_SIZE as f64 / sample_rate as f64).round() as usize).min(nyquist_bin);
+        let edge = |b: usize| MIN_FREQ * (MAX_FREQ / MIN_FREQ).powf(b as f64 / num_bars as f64);
+        let bands = (0..num_bars)
+            .map(|bar| {
+                let lo = bin_of(edge(bar)).min(nyquist_bin - 1);
+                (lo, bin_of(edge(bar + 1)).clamp(lo + 1, nyquist_bin))
+            })
+            .collect();
+        Self {
+            fft: FftPlanner::new().plan_fft_forward(FFT_SIZE),
+            window: (0..FFT_SIZE)
+                .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / FFT_SIZE as f32).cos())
+                .collect(),
+            bands,
+            samples: vec![0.0; FFT_SIZE],
+            next: 0,
+            filled: false,
+            hop: (sample_rate / FRAME_RATE).max(1) as usize,
+            pending: 0,
+            spectrum: vec![Complex::default(); FFT_SIZE],
+        }
+    }
+
+    /// Add a sample; true when it's time for a new frame.
+    fn push(&mut self, sample: f32) -> bool {
+        self.samples[self.next] = sample;
+        self.next = (self.next + 1) % FFT_SIZE;
+        self.filled |= self.next == 0;
+        self.pending += 1;
+        if self.filled && self.pending >= self.hop {
+            self.pending = 0;
+            return true;
+        }
+        false
+    }
+
+    /// Level of each band in dB, where a full-scale sine reads 0.
+    fn band_levels_db(&mut self) -> Vec<f64> {
+        let (newer, older) = self.samples.split_at(self.next);
+        for ((out, sample), w) in self.spectrum.iter_mut().zip(older.iter().chain(newer)).zip(&self.window) {
+            *out = Complex::new(sample * w, 0.0);
+        }
+        self.fft.process(&mut self.spectrum);
+
+        let scale = 4.0 / FFT_SIZE as f64;
+        self.bands
+            .iter()
+            .map(|&(lo, hi)| {
+                let energy: f64 = self.spectrum[lo..hi].iter().map(|c| c.norm_sqr() as f64).sum();
+                20.0 * (energy.sqrt() * scale).max(1e-9).log10()
+            })
+            .collect()
+    }
+
+    /// Forget the samples seen so far.
+    fn reset(&mut self) {
+        self.filled = false;
+        self.next = 0;
+        self.pending = 0;
+    }
+}
+
+/// Turns band levels into bar heights, following the loudest recent band
+/// (shared by both channels) with fast attack and gravity falloff.
+struct BarSmoother {
+    /// Smoothed heights (0.0–1.0) and current fall speeds
+    levels: Vec<f64>,
+    falling: Vec<f64>,
+    reference_db: f64,
+}
+
+impl BarSmoother {
+    fn new(num_bars: usize) -> Self {
+        Self { levels: vec![0.0; num_bars], falling: vec![0.0; num_bars], reference_db: MIN_REFERENCE_DB }
+    }
+
+    fn update(&mut self, levels_db: &[f64]) {
+        let loudest = levels_db.iter().copied().fold(f64::MIN, f64::max);
+        self.reference_db = (self.reference_db - REFERENCE_FALL_DB).max(loudest).max(MIN_REFERENCE_DB);
+        let floor = self.reference_db - RANGE_DB;
+        for (i, db) in levels_db.iter().enumerate() {
+            self.approach(i, ((db - floor) / RANGE_DB).clamp(0.0, 1.0));
+        }
+    }
+
+    /// Drop the bars to zero; false if they already were.
+    fn reset(&mut self) -> bool {
+        if self.levels.iter().all(|&l| l == 0.0) {
+            return false;
+        }
+        self.levels.fill(0.0);
+        self.falling.fill(0.0);
+        true
+    }
+
+    /// With no input, every bar falls towards zero; false if all were there.
+    fn decay(&mut self) -> bool {
+        if self.levels.iter().all(|&l| l == 0.0) {
+            return false;
+        }
+        for i in 0..self.levels.len() {
+            self.approach(i, 0.0);
+        }
+        true
+    }
+
+    fn approach(&mut self, i: usize, target: f64) {
+        let Some(level) = self.levels.get_mut(i) else { return };
+        if target >= *level {
+            *level += (target - *level) * ATTACK;
+            self.falling[i] = 0.0;
+        } else {
+            self.falling[i] += GRAVITY;
+            *level = (*level - self.falling[i]).max(target);
+        }
+    }
+
+    fn publish(&self, bars: &Mutex<Vec<u16>>) {
+        let mut bars = bars.lock().unwrap_or_else(|e| e.into_inner());
+        for (bar, level) in bars.iter_mut().zip(&self.levels) {
+            *bar = (level * BAR_MAX as f64).round() as u16;
+        }
+    }
+}
+
+/// Time constants (seconds) for bars rising towards and falling back from
+/// their target height when drawn.
+const DRAW_ATTACK: f64 = 0.025;
+const DRAW_DECAY: f64 = 0.12;
+
+/// Bounds on the estimated time between backend frames.
+const MIN_FRAME_INTERVAL: f64 = 1.0 / 240.0;
+const MAX_FRAME_INTERVAL: f64 = 0.25;
+
+/// Height changes smaller than this (of a full bar) aren't worth a redraw.
+const SETTLED: f64 = 0.001;
+
+/// Smooths backend frames for drawing at the display's frame rate: each new
+/// frame is eased into over the time frames usually take to arrive, and each
+/// bar follows with its own attack and decay.
+pub struct BarRenderer {
+    /// The last frame received, as it came
+    latest: Vec<u16>,
+    /// Interpolation from the target when the last frame arrived to that frame
+    from: Vec<f64>,
+    to: Vec<f64>,
+    received_at: f64,
+    interval: f64,
+    /// Heights as drawn (0.0–1.0), and when they were last advanced
+    shown: Vec<f64>,
+    shown_at: Option<f64>,
+}
+
+impl BarRenderer {
+    pub fn new(num_bars: usize) -> Self {
+        Self {
+            latest: vec![0; num_bars],
+            from: vec![0.0; num_bars],
+            to: vec![0.0; num_bars],
+            received_at: 0.0,
+            interval: 1.0 / FRAME_RATE as f64,
+            shown: vec![0.0; num_bars],
+            shown_at: None,
+        }
+    }
+
+    /// Whether `bars` differs from the last frame received.
+    pub fn is_new(&self, bars: &[u16]) -> bool {
+        self.latest != bars
+    }
+
+    /// Take the backend's current frame at `now` (seconds); ignored if it's
+    /// the one already received.
+    pub fn receive(&mut self, bars: &[u16], now: f64) {
+        if !self.is_new(bars) {
+            return;
+        }
+        let since = now - self.received_at;
+        if since > 0.0 && since < MAX_FRAME_INTERVAL {
+            self.interval = (self.interval * 0.8 + since * 0.2).clamp(MIN_FRAME_INTERVAL, MAX_FRAME_INTERVAL);
+        }
+        self.from = (0..self.to.len()).map(|i| self.target(i, now)).collect();
+        self.latest.clear();
+        self.latest.extend_from_slice(bars);
+        self.to = bars.iter().map(|&b| b as f64 / BAR_MAX as f64).collect();
+        self.from.resize(self.to.len(), 0.0);
+        self.shown.resize(self.to.len(), 0.0);
+        self.received_at = now;
+    }
+
+    /// Move the drawn heights on to `now`; true if they changed enough to
+    /// redraw.
+    pub fn advance(&mut self, now: f64) -> bool {
+        let dt = self.shown_at.map_or(0.0, |t| (now - t).max(0.0));
+        self.shown_at = Some(now);
+        let mut changed = false;
+        for i in 0..self.shown.len() {
+            let target = self.target(i, now);
+            let shown = self.shown[i];
+            let tau = if target > shown { DRAW_ATTACK } else { DRAW_DECAY };
+            let next = if (target - shown).abs() < SETTLED {
+                target
+            } else {
+                shown + (target - shown) * (1.0 - (-dt / tau).exp())
+            };
+            changed |= (next - shown).abs() >= SETTLED || (next == target && next != shown);
+            self.shown[i] = next;
+        }
+        changed
+    }
+
+    /// True once the drawn heights have caught up with the last frame.
+    pub fn settled(&self, now: f64) -> bool {
+        now - self.received_at >= self.interval && self.shown.iter().zip(&self.to).all(|(s, t)| s == t)
+    }
+
+    /// Heights to draw, 0.0–1.0.
+    pub fn heights(&self) -> &[f64] {
+        &self.shown
+    }
+
+    /// Where bar `i` is heading at `now`, part-way between the last two frames.
+    fn target(&self, i: usize, now: f64) -> f64 {
+        let t = ((now - self.received_at) / self.interval).clamp(0.0, 1.0);
+        let from = self.from.get(i).copied().unwrap_or(0.0);
+        let to = self.to.get(i).copied().unwrap_or(0.0);
+        from + (to - from) * t
+    }
+}
@@ -1,5 +1,20 @@
 use std::path::Path;
-use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::decode::{self, PcmSink};
+use crate::sections::TrackSections;
+
+/// Default bar geometry in logical pixels: each bar is `BAR_WIDTH` wide, `BAR_GAP` apart.
+pub const BAR_WIDTH: i32 = 2;
+pub const BAR_GAP: i32 = 2;
+
+/// Length of one RMS window in the streaming accumulator, in seconds.
+const RMS_WINDOW_SECS: f64 = 0.01;
+
+/// How often partial waveforms are handed out while decoding.
+const PARTIAL_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Normalized stereo peak pair (0.0–1.0)
 #[derive(Clone, Debug)]
@@ -15,169 +30,503 @@ pub struct WaveformData {
 }
 
 impl WaveformData {
-    /// Extract waveform peaks from an audio file using ffmpeg.
-    /// Returns `num_bars` peaks, each normalized 0.0–1.0.
+    /// Calculate the number of bars that fit in a given pixel width.
+    /// Each bar is `bar_width` px wide with `gap` px spacing.
+    pub fn bars_for_width(width: i32, bar_width: i32, gap: i32) -> usize {
+        let block = bar_width + gap;
+        if block <= 0 { return 0; }
+        (width / block) as usize
+    }
+}
+
+/// Bins kept per track in a `WaveformProfile` (and in the disk cache).
+pub const PROFILE_BINS: usize = 2048;
+
+/// Un-normalized RMS levels at a fixed high resolution. Every bar count is
+/// derived from this, so it is what gets decoded once and cached on disk.
+#[derive(Clone, Debug)]
+pub struct WaveformProfile {
+    pub bins: Vec<PeakPair>,
+}
+
+impl WaveformProfile {
+    /// Decode an audio file into a profile of `PROFILE_BINS` bins.
+    /// While decoding, `on_partial` periodically receives the profile so far,
+    /// filled in from the left (only when the track length is known up front).
+    /// Setting `cancel` stops decoding early and returns None. `tap` sees the
+    /// same audio, so other analyses can share the decode.
     /// This is CPU-intensive and should be called from a background thread.
-    pub fn from_file(path: &str, num_bars: usize) -> Option<Self> {
+    pub fn from_file<'a>(
+        path: &str,
+        cancel: &'a AtomicBool,
+        on_partial: &'a mut dyn FnMut(WaveformProfile),
+        tap: Option<&'a mut dyn PcmSink>,
+    ) -> Option<Self> {
         if !Path::new(path).exists() {
             return None;
         }
+        let mut sink = ProgressiveSink {
+            acc: RmsAccumulator::default(),
+            cancel,
+            on_partial,
+            tap,
+            frames: 0,
+            last_partial: Instant::now(),
+        };
+        if !decode::decode_stereo(Path::new(path), &mut sink) {
+            return None;
+        }
+        Some(Self { bins: sink.acc.bin(PROFILE_BINS)? })
+    }
+}
+
+/// Dense waveform levels for cheap re-binning at any width: level 0 is the
+/// full profile and each level above it halves the resolution.
+#[derive(Clone, Debug)]
+pub struct PeakPyramid {
+    levels: Vec<Vec<PeakPair>>,
+}
+
+impl PeakPyramid {
+    pub fn new(profile: &WaveformProfile) -> Self {
+        let mut levels = vec![profile.bins.clone()];
+        while let Some(top) = levels.last().filter(|l| l.len() > 1) {
+            let next = top.chunks(2).map(merge_rms).collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// `num_bars` bars, normalized for drawing. Merges from the coarsest
+    /// level that still has a few bins per bar, so bars stay evenly weighted.
+    pub fn bars(&self, num_bars: usize) -> WaveformData {
+        let level = self.levels
+            .iter()
+            .rev()
+            .find(|l| l.len() >= num_bars * 4)
+            .unwrap_or(&self.levels[0]);
+        let n = level.len();
+        if n == 0 || num_bars == 0 {
+            return WaveformData { peaks: Vec::new() };
+        }
+        let mut peaks: Vec<PeakPair> = (0..num_bars)
+            .map(|i| {
+                let start = (i * n / num_bars).min(n - 1);
+                let end = ((i + 1) * n / num_bars).clamp(start + 1, n);
+                merge_rms(&level[start..end])
+            })
+            .collect();
+        normalize_peaks(&mut peaks);
+        WaveformData { peaks }
+    }
+}
+
+/// Bins cover equal time spans, so the merged RMS is the root of their mean square.
+fn merge_rms(bins: &[PeakPair]) -> PeakPair {
+    let n = bins.len().max(1) as f64;
+    let left = bins.iter().map(|b| b.left * b.left).sum::<f64>() / n;
+    let right = bins.iter().map(|b| b.right * b.right).sum::<f64>() / n;
+    PeakPair { left: left.sqrt(), right: right.sqrt() }
+}
+
+/// Streaming RMS accumulator: folds decoded frames into short fixed windows
+/// of squared-sample sums, so memory stays small however long the track is.
+/// The windows are merged into bars once decoding finishes.
+#[derive(Default)]
+pub struct RmsAccumulator {
+    frames_per_window: usize,
+    // Expected number of windows, when the decoder knows the track length
+    total_windows: Option<usize>,
+    // (sum of left², sum of right², frame count) per finished window
+    windows: Vec<(f64, f64, usize)>,
+    current: (f64, f64, usize),
+}
 
-        // Use ffmpeg to decode audio to raw signed 16-bit stereo PCM at 8kHz
-        // (low sample rate = fast extraction, still enough resolution for waveform)
-        let output = Command::new("ffmpeg")
-            .args(&[
-                "-i", path,
-                "-ac", "2",          // stereo
-                "-ar", "8000",       // 8kHz sample rate
-                "-f", "s16le",       // raw signed 16-bit little-endian
-                "-acodec", "pcm_s16le",
-                "-v", "quiet",
-                "-",                 // output to stdout
-            ])
-            .output()
-            .ok()?;
-
-        if !output.status.success() || output.stdout.is_empty() {
+impl RmsAccumulator {
+    /// Merge the windows into `num_bars` RMS values. None if nothing was decoded.
+    pub fn bin(mut self, num_bars: usize) -> Option<Vec<PeakPair>> {
+        if self.current.2 > 0 {
+            self.windows.push(self.current);
+        }
+        let n = self.windows.len();
+        if n == 0 || num_bars == 0 {
             return None;
         }
 
-        let raw = &output.stdout;
-        // Each sample frame = 4 bytes (2 bytes left + 2 bytes right, s16le)
-        let num_frames = raw.len() / 4;
-        if num_frames == 0 || num_bars == 0 {
+        let peaks = (0..num_bars)
+            .map(|i| {
+                let start = (i * n / num_bars).min(n - 1);
+                let end = ((i + 1) * n / num_bars).clamp(start + 1, n);
+                rms_of_windows(&self.windows[start..end])
+            })
+            .collect();
+        Some(peaks)
+    }
+
+    /// `num_bars` bins laid out over the whole expected track, with the bins
+    /// not fully decoded yet left at zero. None when the length is unknown.
+    pub fn snapshot(&self, num_bars: usize) -> Option<Vec<PeakPair>> {
+        let n = self.total_windows?.max(self.windows.len());
+        if n == 0 || num_bars == 0 {
             return None;
         }
+        let done = self.windows.len();
+        let peaks = (0..num_bars)
+            .map(|i| {
+                let start = (i * n / num_bars).min(n - 1);
+                let end = ((i + 1) * n / num_bars).clamp(start + 1, n);
+                if end <= done {
+                    rms_of_windows(&self.windows[start..end])
+                } else {
+                    PeakPair { left: 0.0, right: 0.0 }
+                }
+            })
+            .collect();
+        Some(peaks)
+    }
+}
 
-        let frames_per_bar = (num_frames as f64 / num_bars as f64).max(1.0);
-        let mut peaks = Vec::with_capacity(num_bars);
+impl PcmSink for RmsAccumulator {
+    fn begin(&mut self, sample_rate: u32, total_frames: Option<u64>, _channels: usize) {
+        self.frames_per_window = ((sample_rate as f64 * RMS_WINDOW_SECS) as usize).max(1);
+        self.total_windows = total_frames.map(|f| (f as usize).div_ceil(self.frames_per_window));
+        self.windows.clear();
+        self.current = (0.0, 0.0, 0);
+    }
 
-        let mut frame_idx: f64 = 0.0;
-        for _ in 0..num_bars {
-            let start = frame_idx as usize;
-            let end = ((frame_idx + frames_per_bar) as usize).min(num_frames);
+    fn frame(&mut self, left: f32, right: f32) {
+        let (l, r) = (left as f64, right as f64);
+        self.current.0 += l * l;
+        self.current.1 += r * r;
+        self.current.2 += 1;
+        if self.current.2 >= self.frames_per_window {
+            self.windows.push(self.current);
+            self.current = (0.0, 0.0, 0);
+        }
+    }
+}
 
-            let mut sum_left: f64 = 0.0;
-            let mut sum_right: f64 = 0.0;
-            let mut count: f64 = 0.0;
+/// Wraps the accumulator to hand out partial profiles while decoding.
+struct ProgressiveSink<'a> {
+    acc: RmsAccumulator,
+    cancel: &'a AtomicBool,
+    on_partial: &'a mut dyn FnMut(WaveformProfile),
+    tap: Option<&'a mut dyn PcmSink>,
+    frames: usize,
+    last_partial: Instant,
+}
 
-            for f in start..end {
-                let offset = f * 4;
-                if offset + 3 >= raw.len() {
-                    break;
-                }
-                let left = i16::from_le_bytes([raw[offset], raw[offset + 1]]);
-                let right = i16::from_le_bytes([raw[offset + 2], raw[offset + 3]]);
-                let l = left.unsigned_abs() as f64;
-                let r = right.unsigned_abs() as f64;
-                sum_left += l * l;
-                sum_right += r * r;
-                count += 1.0;
+impl PcmSink for ProgressiveSink<'_> {
+    fn begin(&mut self, sample_rate: u32, total_frames: Option<u64>, channels: usize) {
+        self.acc.begin(sample_rate, total_frames, channels);
+        if let Some(tap) = self.tap.as_mut() {
+            tap.begin(sample_rate, total_frames, channels);
+        }
+        self.frames = 0;
+    }
+
+    fn frame(&mut self, left: f32, right: f32) {
+        self.acc.frame(left, right);
+        if let Some(tap) = self.tap.as_mut() {
+            tap.frame(left, right);
+        }
+        self.frames += 1;
+        // Checking the clock every frame would dominate the decode loop
+        if self.frames.is_multiple_of(4096) && self.last_partial.elapsed() >= PARTIAL_INTERVAL {
+            self.last_partial = Instant::now();
+            if let Some(bins) = self.acc.snapshot(PROFILE_BINS) {
+                (self.on_partial)(WaveformProfile { bins });
             }
+        }
+    }
 
-            // RMS (root mean square) gives a more musical representation
-            let rms_left = if count > 0.0 { (sum_left / count).sqrt() } else { 0.0 };
-            let rms_right = if count > 0.0 { (sum_right / count).sqrt() } else { 0.0 };
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+fn rms_of_windows(windows: &[(f64, f64, usize)]) -> PeakPair {
+    let (sum_l, sum_r, count) = windows
+        .iter()
+        .fold((0.0, 0.0, 0), |acc, w| (acc.0 + w.0, acc.1 + w.1, acc.2 + w.2));
+    // RMS (root mean square) gives a more musical representation
+    let count = count.max(1) as f64;
+    PeakPair { left: (sum_l / count).sqrt(), right: (sum_r / count).sqrt() }
+}
+
+/// Normalize using the 95th percentile so only the loudest bars peak,
+/// then apply a power curve to spread out the dynamic range.
+fn normalize_peaks(peaks: &mut [PeakPair]) {
+    let mut all_vals: Vec<f64> = peaks.iter()
+        .flat_map(|p| [p.left, p.right])
+        .filter(|v| *v > 0.0)
+        .collect();
+    all_vals.sort_by(|a, b| a.partial_cmp(b).unwrap());
 
-            peaks.push(PeakPair {
-                left: rms_left,
-                right: rms_right,
-            });
+    let norm_val = if !all_vals.is_empty() {
+        let idx = ((all_vals.len() as f64) * 0.95) as usize;
+        let idx = idx.min(all_vals.len() - 1);
+        all_vals[idx]
+    } else {
+        1.0
+    };
 
-            frame_idx += frames_per_bar;
+    if norm_val > 0.0 {
+        for p in peaks.iter_mut() {
+            // Normalize against 95th percentile (top 5% clips to 1.0)
+            p.left = (p.left / norm_val).min(1.0);
+            p.right = (p.right / norm_val).min(1.0);
+            // Cube-root power curve: stretches lows, compresses highs
+            p.left = p.left.powf(1.8);
+            p.right = p.right.powf(1.8);
         }
+    }
+}
 
-        // Normalize using the 95th percentile so only the loudest bars peak,
-        // then apply a power curve to spread out the dynamic range.
-        let mut all_vals: Vec<f64> = peaks.iter()
-            .flat_map(|p| [p.left, p.right])
-            .filter(|v| *v > 0.0)
-            .collect();
-        all_vals.sort_by(|a, b| a.partial_cmp(b).unwrap());
-
-        let norm_val = if !all_vals.is_empty() {
-            let idx = ((all_vals.len() as f64) * 0.95) as usize;
-            let idx = idx.min(all_vals.len() - 1);
-            all_vals[idx]
-        } else {
-            1.0
-        };
+/// How the waveform is drawn, from `[waveform] style` in the config.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WaveformKind {
+    /// Flat-ended bars, left channel up and right channel down
+    Mirrored,
+    /// Same bars with rounded ends
+    Rounded,
+    /// One filled outline through the bar tops
+    Envelope,
+    /// The envelope's outline only
+    Line,
+}
+
+/// Waveform appearance; see `[waveform]` in the README for the config keys.
+#[derive(Clone, Copy, Debug)]
+pub struct WaveformStyle {
+    pub kind: WaveformKind,
+    pub bar_width: i32,
+    pub gap: i32,
+    /// Smallest drawn height in px, so silent stretches stay visible
+    pub min_height: f64,
+}
 
-        if norm_val > 0.0 {
-            for p in peaks.iter_mut() {
-                // Normalize against 95th percentile (top 5% clips to 1.0)
-                p.left = (p.left / norm_val).min(1.0);
-                p.right = (p.right / norm_val).min(1.0);
-                // Cube-root power curve: stretches lows, compresses highs
-                p.left = p.left.powf(1.8);
-                p.right = p.right.powf(1.8);
+impl Default for WaveformStyle {
+    fn default() -> Self {
+        Self { kind: WaveformKind::Mirrored, bar_width: BAR_WIDTH, gap: BAR_GAP, min_height: 2.0 }
+    }
+}
+
+impl WaveformStyle {
+    pub fn from_config(config: &Config) -> Self {
+        let default = Self::default();
+        let kind = match config.get("waveform", "style").map(str::to_lowercase).as_deref() {
+            None | Some("mirrored") | Some("bars") => WaveformKind::Mirrored,
+            Some("rounded") => WaveformKind::Rounded,
+            Some("envelope") | Some("filled") => WaveformKind::Envelope,
+            Some("line") => WaveformKind::Line,
+            Some(other) => {
+                log::warn!("Unknown waveform style '{}', using mirrored", other);
+                WaveformKind::Mirrored
             }
+        };
+        let number = |key: &str| config.get("waveform", key).and_then(|v| v.parse::<f64>().ok());
+        Self {
+            kind,
+            bar_width: number("bar_width").map_or(default.bar_width, |v| (v as i32).clamp(1, 32)),
+            gap: number("bar_gap").map_or(default.gap, |v| (v as i32).clamp(0, 32)),
+            min_height: number("min_height").map_or(default.min_height, |v| v.clamp(0.0, 48.0)),
         }
+    }
 
-        Some(WaveformData { peaks })
+    /// Number of bars (or envelope points) that fit in `width` px.
+    pub fn bars_for_width(&self, width: i32) -> usize {
+        WaveformData::bars_for_width(width, self.bar_width, self.gap)
     }
+}
 
-    /// Calculate the number of bars that fit in a given pixel width.
-    /// Each bar is `bar_width` px wide with `gap` px spacing.
-    pub fn bars_for_width(width: i32, bar_width: i32, gap: i32) -> usize {
-        let block = bar_width + gap;
-        if block <= 0 { return 0; }
-        (width / block) as usize
+/// RGBA colours for the waveform, derived from the album palette.
+#[derive(Clone, Copy, Debug)]
+pub struct WaveformColors {
+    pub played: (f64, f64, f64, f64),
+    pub unplayed: (f64, f64, f64, f64),
+    pub playhead: (f64, f64, f64, f64),
+    pub hover: (f64, f64, f64, f64),
+}
+
+impl WaveformColors {
+    /// The background gradient comes from the same palette, so colours are
+    /// brightened like the CAVA bars and then lifted towards white to stay legible.
+    /// `palette` is [top-left, top-right, bottom-left, bottom-right].
+    pub fn from_palette(palette: &[(f64, f64, f64); 4]) -> Self {
+        let lift = |(r, g, b): (f64, f64, f64), white: f64| {
+            let channel = |c: f64| {
+                let bright = (c * 1.6).min(1.0);
+                bright + (1.0 - bright) * white
+            };
+            (channel(r), channel(g), channel(b))
+        };
+        let (pr, pg, pb) = lift(palette[1], 0.45);
+        let (ur, ug, ub) = lift(palette[2], 0.7);
+        Self {
+            played: (pr, pg, pb, 0.95),
+            unplayed: (ur, ug, ub, 0.28),
+            playhead: (1.0, 1.0, 1.0, 0.9),
+            hover: (1.0, 1.0, 1.0, 0.4),
+        }
     }
 }
 
 /// Draw the waveform onto a Cairo context.
 /// - `peaks`: the peak data
-/// - `position`: 0.0–1.0 playback position
+/// - `position`: 0.0–1.0 playback position; fractional bars are split at the playhead
 /// - `w`, `h`: widget dimensions
-/// - `played_color`: (r, g, b, a) for played bars
-/// - `unplayed_color`: (r, g, b, a) for unplayed bars
 pub fn draw_waveform(
     cr: &cairo::Context,
     peaks: &[PeakPair],
     position: f64,
     w: f64,
     h: f64,
+    style: &WaveformStyle,
+    colors: &WaveformColors,
 ) {
     if peaks.is_empty() || w <= 0.0 || h <= 0.0 {
         return;
     }
 
-    let bar_width: f64 = 2.0;
-    let gap: f64 = 2.0;
-    let block = bar_width + gap;
-    let center_y = h / 2.0;
+    let (offset_x, waveform_width) = layout(peaks.len(), w, style);
+    let cursor_x = offset_x + position.clamp(0.0, 1.0) * waveform_width;
 
-    let n_bars = peaks.len();
-    let waveform_width = n_bars as f64 * block;
-    let offset_x = (w - waveform_width).max(0.0) / 2.0;
-    let cursor_x = position.clamp(0.0, 1.0) * waveform_width;
+    // Everything in the unplayed colour, then the played part again on top,
+    // clipped at the playhead so it moves smoothly rather than a bar at a time
+    let set_rgba = |(r, g, b, a): (f64, f64, f64, f64)| cr.set_source_rgba(r, g, b, a);
+    set_rgba(colors.unplayed);
+    paint_shape(cr, peaks, offset_x, h, style);
 
-    // Minimum bar height so empty bars are still visible
-    let min_bar_h = 2.0;
+    let _ = cr.save();
+    cr.rectangle(0.0, 0.0, cursor_x, h);
+    cr.clip();
+    set_rgba(colors.played);
+    paint_shape(cr, peaks, offset_x, h, style);
+    let _ = cr.restore();
 
-    for (i, peak) in peaks.iter().enumerate() {
-        let x = offset_x + i as f64 * block;
-        let bar_x = x - offset_x; // position within waveform
+    if position > 0.0 {
+        set_rgba(colors.playhead);
+        cr.rectangle(cursor_x - 0.5, 0.0, 1.0, h);
+        let _ = cr.fill();
+    }
+}
+
+/// Left edge and width of `num_bars` bars centred in a `w`-wide area.
+fn layout(num_bars: usize, w: f64, style: &WaveformStyle) -> (f64, f64) {
+    let width = num_bars as f64 * (style.bar_width + style.gap) as f64;
+    ((w - width).max(0.0) / 2.0, width)
+}
+
+/// Shade the silent start and end of the track and mark its section
+/// boundaries, lined up with a waveform of `num_bars` bars.
+pub fn draw_sections(
+    cr: &cairo::Context,
+    sections: &TrackSections,
+    num_bars: usize,
+    w: f64,
+    h: f64,
+    style: &WaveformStyle,
+    colors: &WaveformColors,
+) {
+    if num_bars == 0 {
+        return;
+    }
+    let (offset_x, width) = layout(num_bars, w, style);
+    let x_at = |position: f64| offset_x + position.clamp(0.0, 1.0) * width;
+
+    cr.set_source_rgba(0.0, 0.0, 0.0, 0.3);
+    if sections.leading_silence > 0.0 {
+        cr.rectangle(offset_x, 0.0, x_at(sections.leading_silence) - offset_x, h);
+    }
+    if sections.trailing_silence < 1.0 {
+        let x = x_at(sections.trailing_silence);
+        cr.rectangle(x, 0.0, offset_x + width - x, h);
+    }
+    let _ = cr.fill();
 
-        // Left peak goes up from center, right goes down
-        let left_h = (peak.left * (h / 2.0 - 1.0)).max(min_bar_h / 2.0);
-        let right_h = (peak.right * (h / 2.0 - 1.0)).max(min_bar_h / 2.0);
-        let total_h = left_h + right_h;
-        let y = center_y - left_h;
+    // A faint line through the waveform with a small notch on top
+    let (r, g, b, a) = colors.playhead;
+    for marker in &sections.markers {
+        let x = x_at(marker.position).round();
+        cr.set_source_rgba(r, g, b, a * 0.35);
+        cr.rectangle(x - 0.5, 0.0, 1.0, h);
+        let _ = cr.fill();
+        cr.set_source_rgba(r, g, b, a * 0.8);
+        cr.move_to(x - 3.0, 0.0);
+        cr.line_to(x + 3.0, 0.0);
+        cr.line_to(x, 4.0);
+        cr.close_path();
+        let _ = cr.fill();
+    }
+}
+
+/// Draw the pointer's seek preview: a faint line at `x`.
+pub fn draw_hover_line(cr: &cairo::Context, x: f64, w: f64, h: f64, colors: &WaveformColors) {
+    let (r, g, b, a) = colors.hover;
+    cr.set_source_rgba(r, g, b, a);
+    cr.rectangle(x.clamp(0.0, w) - 0.5, 0.0, 1.0, h);
+    let _ = cr.fill();
+}
 
-        if bar_x < cursor_x {
-            // Played: bright white
-            cr.set_source_rgba(1.0, 1.0, 1.0, 0.9);
-        } else {
-            // Unplayed: dim white
-            cr.set_source_rgba(1.0, 1.0, 1.0, 0.25);
+/// Trace the waveform in `style` and fill (or stroke) it with the current source.
+fn paint_shape(cr: &cairo::Context, peaks: &[PeakPair], offset_x: f64, h: f64, style: &WaveformStyle) {
+    let bar_width = style.bar_width as f64;
+    let block = bar_width + style.gap as f64;
+    let center_y = h / 2.0;
+    let half_min = style.min_height / 2.0;
+    // Left peak goes up from center, right goes down
+    let extent = |peak: &PeakPair| {
+        let up = (peak.left * (h / 2.0 - 1.0)).max(half_min);
+        let down = (peak.right * (h / 2.0 - 1.0)).max(half_min);
+        (up, down)
+    };
+
+    match style.kind {
+        WaveformKind::Mirrored | WaveformKind::Rounded => {
+            let radius = if style.kind == WaveformKind::Rounded { bar_width / 2.0 } else { 0.0 };
+            for (i, peak) in peaks.iter().enumerate() {
+                let x = offset_x + i as f64 * block;
+                let (up, down) = extent(peak);
+                rounded_rect(cr, x, center_y - up, bar_width, up + down, radius);
+            }
+            let _ = cr.fill();
         }
+        WaveformKind::Envelope | WaveformKind::Line => {
+            // Through the bar centres: along the top, then back along the bottom
+            let mid = |i: usize| offset_x + i as f64 * block + bar_width / 2.0;
+            for (i, peak) in peaks.iter().enumerate() {
+                cr.line_to(mid(i), center_y - extent(peak).0);
+            }
+            for (i, peak) in peaks.iter().enumerate().rev() {
+                cr.line_to(mid(i), center_y + extent(peak).1);
+            }
+            cr.close_path();
+            if style.kind == WaveformKind::Envelope {
+                let _ = cr.fill();
+            } else {
+                cr.set_line_width(1.5);
+                cr.set_line_join(cairo::LineJoin::Round);
+                let _ = cr.stroke();
+            }
+        }
+    }
+}
 
-        // Draw rounded-ish bar (just a rect at 2px wide)
-        cr.rectangle(x, y, bar_width, total_h);
-        cr.fill().unwrap();
+/// Add a rectangle with corners rounded by up to `radius` to the current path.
+fn rounded_rect(cr: &cairo::Context, x: f64, y: f64, w: f64, h: f64, radius: f64) {
+    use std::f64::consts::PI;
+    let r = radius.min(w / 2.0).min(h / 2.0);
+    if r <= 0.0 {
+        cr.rectangle(x, y, w, h);
+        return;
     }
+    cr.new_sub_path();
+    cr.arc(x + w - r, y + r, r, -PI / 2.0, 0.0);
+    cr.arc(x + w - r, y + h - r, r, 0.0, PI / 2.0);
+    cr.arc(x + r, y + h - r, r, PI / 2.0, PI);
+    cr.arc(x + r, y + r, r, PI, 1.5 * PI);
+    cr.close_path();
 }
 
 /// Draw a placeholder waveform (no peaks loaded yet) as small dots
@@ -185,17 +534,20 @@ pub fn draw_placeholder(
     cr: &cairo::Context,
     w: f64,
     h: f64,
+    style: &WaveformStyle,
+    colors: &WaveformColors,
 ) {
-    let bar_width: f64 = 2.0;
-    let gap: f64 = 2.0;
+    let bar_width = style.bar_width as f64;
+    let gap = style.gap as f64;
     let block = bar_width + gap;
     let center_y = h / 2.0;
 
     let mut x = gap;
-    cr.set_source_rgba(1.0, 1.0, 1.0, 0.3);
+    let (r, g, b, a) = colors.unplayed;
+    cr.set_source_rgba(r, g, b, a);
     while x < w - gap {
         cr.rectangle(x, center_y - 1.0, bar_width, 2.0);
-        cr.fill().unwrap();
         x += block;
     }
+    let _ = cr.fill();
 }
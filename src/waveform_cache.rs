@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::loudness::TrackLoudness;
+use crate::spectrogram::{Spectrogram, SPECTROGRAM_BANDS, SPECTROGRAM_COLUMNS};
+use crate::track_analysis::{MusicalKey, TrackAnalysis};
+use crate::waveform::{PeakPair, WaveformProfile};
+
+const MAGIC: &[u8; 6] = b"BARDWF";
+const LOUDNESS_MAGIC: &[u8; 6] = b"BARDLD";
+const SPECTROGRAM_MAGIC: &[u8; 6] = b"BARDSG";
+const ANALYSIS_MAGIC: &[u8; 6] = b"BARDTA";
+/// Bump whenever the file layout or the profile's meaning changes;
+/// entries from other versions are treated as misses and overwritten.
+const VERSION: u16 = 1;
+
+/// Keep at most this many cached waveforms (~16 KB each), and as many
+/// loudness entries, spectrograms (~64 KB each) and tempo/key results.
+const MAX_ENTRIES: usize = 4000;
+
+/// File extensions of the entry kinds, each trimmed to `MAX_ENTRIES` on its own.
+const ENTRY_KINDS: [&str; 4] = ["wf", "ld", "sg", "ta"];
+
+/// Numbers temp files so concurrent writers (threads or Bard instances) never share one.
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Temp files older than this were left by a crashed writer; eviction removes them.
+const STALE_TMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Identifies one version of a track: its path relative to the music folder
+/// plus size and mtime, so edited or replaced files are decoded again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CacheKey {
+    rel_path: String,
+    size: u64,
+    mtime: u64,
+}
+
+impl CacheKey {
+    /// Build the key for `rel_path` from the metadata of the file at `full_path`.
+    pub fn new(rel_path: &str, full_path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(full_path).ok()?;
+        let mtime = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Some(Self { rel_path: rel_path.to_string(), size: meta.len(), mtime })
+    }
+
+    /// FNV-1a of the key fields; stable across builds, unlike `DefaultHasher`.
+    fn file_name(&self, ext: &str) -> String {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let bytes = self.rel_path.bytes()
+            .chain(self.size.to_le_bytes())
+            .chain(self.mtime.to_le_bytes());
+        for b in bytes {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}.{}", hash, ext)
+    }
+}
+
+/// Versioned binary cache of per-track analyses: waveform profiles (`.wf`),
+/// loudness measurements (`.ld`), spectrograms (`.sg`) and tempo/key
+/// estimates (`.ta`), one file of each per track.
+///
+/// Layout (little-endian): magic, version u16, size u64, mtime u64,
+/// path length u32 + UTF-8 path, then for waveforms a bin count u32 and
+/// (left, right) f32 pairs; for loudness the true peak f64, a sub-block
+/// count u32 and f32 energies; for spectrograms a cell count u32 (0 for a
+/// track too short to have one) and u8 levels; for tempo/key the BPM f64
+/// (0 if unknown) and the key u8 (tonic, plus 12 if minor; 255 if unknown).
+/// The key is stored in full so hash collisions read as misses.
+#[derive(Clone, Debug)]
+pub struct WaveformCache {
+    dir: PathBuf,
+}
+
+impl WaveformCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn load(&self, key: &CacheKey) -> Option<WaveformProfile> {
+        decode_entry(&self.read(key, "wf")?, key)
+    }
+
+    /// Whether the waveform, spectrogram and tempo/key for `key` all exist
+    /// (so a decode would add nothing), without reading them.
+    pub fn contains(&self, key: &CacheKey) -> bool {
+        ["wf", "sg", "ta"].iter().all(|ext| self.dir.join(key.file_name(ext)).exists())
+    }
+
+    pub fn store(&self, key: &CacheKey, profile: &WaveformProfile) -> io::Result<()> {
+        self.write(key, "wf", &encode_entry(key, profile))
+    }
+
+    pub fn load_loudness(&self, key: &CacheKey) -> Option<TrackLoudness> {
+        decode_loudness(&self.read(key, "ld")?, key)
+    }
+
+    pub fn store_loudness(&self, key: &CacheKey, loudness: &TrackLoudness) -> io::Result<()> {
+        self.write(key, "ld", &encode_loudness(key, loudness))
+    }
+
+    /// `Some(None)` is a cached result too: the track is too short to have
+    /// a spectrogram, so decoding it again would find none either.
+    pub fn load_spectrogram(&self, key: &CacheKey) -> Option<Option<Spectrogram>> {
+        decode_spectrogram(&self.read(key, "sg")?, key)
+    }
+
+    pub fn store_spectrogram(&self, key: &CacheKey, spectrogram: Option<&Spectrogram>) -> io::Result<()> {
+        self.write(key, "sg", &encode_spectrogram(key, spectrogram))
+    }
+
+    pub fn load_analysis(&self, key: &CacheKey) -> Option<TrackAnalysis> {
+        decode_analysis(&self.read(key, "ta")?, key)
+    }
+
+    pub fn store_analysis(&self, key: &CacheKey, analysis: &TrackAnalysis) -> io::Result<()> {
+        self.write(key, "ta", &encode_analysis(key, analysis))
+    }
+
+    /// Copy every entry cached under `old` to `new`, for a file whose tags
+    /// changed but whose audio didn't, so it isn't decoded again.
+    pub fn carry_over(&self, old: &CacheKey, new: &CacheKey) {
+        let results = [
+            self.load(old).map(|profile| self.store(new, &profile)),
+            self.load_loudness(old).map(|loudness| self.store_loudness(new, &loudness)),
+            self.load_spectrogram(old).map(|spectrogram| self.store_spectrogram(new, spectrogram.as_ref())),
+            self.load_analysis(old).map(|analysis| self.store_analysis(new, &analysis)),
+        ];
+        for e in results.into_iter().flatten().filter_map(Result::err) {
+            log::warn!("Failed to carry over cached analysis: {}", e);
+        }
+    }
+
+    fn read(&self, key: &CacheKey, ext: &str) -> Option<Vec<u8>> {
+        let path = self.dir.join(key.file_name(ext));
+        let mut data = Vec::new();
+        File::open(&path).ok()?.read_to_end(&mut data).ok()?;
+
+        // Reads count as use: eviction drops the least recently used entries
+        if let Ok(file) = File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(data)
+    }
+
+    fn write(&self, key: &CacheKey, ext: &str, data: &[u8]) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(key.file_name(ext));
+        // Write then rename so a concurrent reader never sees half a file
+        let n = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let tmp = path.with_extension(format!("{}.{}-{}.tmp", ext, std::process::id(), n));
+        let written = File::create(&tmp)
+            .and_then(|mut file| file.write_all(data))
+            .and_then(|_| std::fs::rename(&tmp, &path));
+        if written.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+        written
+    }
+
+    /// Trim each kind of entry to 90% of `MAX_ENTRIES`, least recently used
+    /// first, and remove stale temp files. This scans the whole directory, so
+    /// call it once after a decode or scan has stored its results rather than
+    /// after every write.
+    pub fn evict(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else { return };
+        let mut kinds: HashMap<String, Vec<(SystemTime, PathBuf)>> = HashMap::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(modified) = entry.metadata().ok().and_then(|m| m.modified().ok()) else {
+                continue;
+            };
+            let Some(ext) = path.extension().and_then(|e| e.to_str()) else { continue };
+            if ext == "tmp" {
+                if modified.elapsed().is_ok_and(|age| age > STALE_TMP_AGE) {
+                    let _ = std::fs::remove_file(&path);
+                }
+                continue;
+            }
+            if ENTRY_KINDS.contains(&ext) {
+                kinds.entry(ext.to_string()).or_default().push((modified, path));
+            }
+        }
+        for mut files in kinds.into_values() {
+            if files.len() <= MAX_ENTRIES {
+                continue;
+            }
+            files.sort();
+            let excess = files.len() - MAX_ENTRIES * 9 / 10;
+            for (_, path) in files.into_iter().take(excess) {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// Magic, version and the full key, shared by all entry kinds.
+fn encode_header(magic: &[u8; 6], key: &CacheKey) -> Vec<u8> {
+    let mut out = Vec::with_capacity(32 + key.rel_path.len());
+    out.extend_from_slice(magic);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.extend_from_slice(&key.size.to_le_bytes());
+    out.extend_from_slice(&key.mtime.to_le_bytes());
+    out.extend_from_slice(&(key.rel_path.len() as u32).to_le_bytes());
+    out.extend_from_slice(key.rel_path.as_bytes());
+    out
+}
+
+fn encode_entry(key: &CacheKey, profile: &WaveformProfile) -> Vec<u8> {
+    let mut out = encode_header(MAGIC, key);
+    out.extend_from_slice(&(profile.bins.len() as u32).to_le_bytes());
+    for bin in &profile.bins {
+        out.extend_from_slice(&(bin.left as f32).to_le_bytes());
+        out.extend_from_slice(&(bin.right as f32).to_le_bytes());
+    }
+    out
+}
+
+fn encode_loudness(key: &CacheKey, loudness: &TrackLoudness) -> Vec<u8> {
+    let mut out = encode_header(LOUDNESS_MAGIC, key);
+    out.extend_from_slice(&loudness.true_peak.to_le_bytes());
+    out.extend_from_slice(&(loudness.sub_blocks.len() as u32).to_le_bytes());
+    for energy in &loudness.sub_blocks {
+        out.extend_from_slice(&(*energy as f32).to_le_bytes());
+    }
+    out
+}
+
+fn encode_spectrogram(key: &CacheKey, spectrogram: Option<&Spectrogram>) -> Vec<u8> {
+    let levels = spectrogram.map_or(&[][..], |s| s.levels.as_slice());
+    let mut out = encode_header(SPECTROGRAM_MAGIC, key);
+    out.extend_from_slice(&(levels.len() as u32).to_le_bytes());
+    out.extend_from_slice(levels);
+    out
+}
+
+fn encode_analysis(key: &CacheKey, analysis: &TrackAnalysis) -> Vec<u8> {
+    let mut out = encode_header(ANALYSIS_MAGIC, key);
+    out.extend_from_slice(&analysis.bpm.unwrap_or(0.0).to_le_bytes());
+    out.push(analysis.key.map_or(u8::MAX, |k| k.tonic + if k.minor { 12 } else { 0 }));
+    out
+}
+
+/// Reads little-endian fields off the front of an entry; None once it runs out.
+struct Fields<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.rest.len() < n {
+            return None;
+        }
+        let (head, tail) = self.rest.split_at(n);
+        self.rest = tail;
+        Some(head)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        Some(f64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    /// Check magic, version and key; None on any mismatch.
+    fn header(&mut self, magic: &[u8; 6], key: &CacheKey) -> Option<()> {
+        if self.take(magic.len())? != magic || self.u16()? != VERSION {
+            return None;
+        }
+        let size = self.u64()?;
+        let mtime = self.u64()?;
+        let path_len = self.u32()? as usize;
+        let rel_path = std::str::from_utf8(self.take(path_len)?).ok()?;
+        (size == key.size && mtime == key.mtime && rel_path == key.rel_path).then_some(())
+    }
+}
+
+fn f32_at(c: &[u8]) -> f64 {
+    f32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f64
+}
+
+/// Parse an entry, returning None on any mismatch (magic, version, key) or truncation.
+fn decode_entry(data: &[u8], key: &CacheKey) -> Option<WaveformProfile> {
+    let mut fields = Fields { rest: data };
+    fields.header(MAGIC, key)?;
+    let count = fields.u32()? as usize;
+    let bins = fields
+        .take(count * 8)?
+        .chunks_exact(8)
+        .map(|c| PeakPair { left: f32_at(&c[..4]), right: f32_at(&c[4..]) })
+        .collect();
+    Some(WaveformProfile { bins })
+}
+
+fn decode_loudness(data: &[u8], key: &CacheKey) -> Option<TrackLoudness> {
+    let mut fields = Fields { rest: data };
+    fields.header(LOUDNESS_MAGIC, key)?;
+    let true_peak = fields.f64()?;
+    let count = fields.u32()? as usize;
+    let sub_blocks = fields.take(count * 4)?.chunks_exact(4).map(f32_at).collect();
+    Some(TrackLoudness { sub_blocks, true_peak })
+}
+
+fn decode_spectrogram(data: &[u8], key: &CacheKey) -> Option<Option<Spectrogram>> {
+    let mut fields = Fields { rest: data };
+    fields.header(SPECTROGRAM_MAGIC, key)?;
+    let count = fields.u32()? as usize;
+    if count == 0 {
+        return Some(None);
+    }
+    // A different resolution is as good as a miss
+    if count != SPECTROGRAM_COLUMNS * SPECTROGRAM_BANDS {
+        return None;
+    }
+    Some(Some(Spectrogram { levels: fields.take(count)?.to_vec() }))
+}
+
+fn decode_analysis(data: &[u8], key: &CacheKey) -> Option<TrackAnalysis> {
+    let mut fields = Fields { rest: data };
+    fields.header(ANALYSIS_MAGIC, key)?;
+    let bpm = fields.f64()?;
+    let musical_key = match fields.take(1)?[0] {
+        k @ 0..=23 => Some(MusicalKey { tonic: k % 12, minor: k >= 12 }),
+        _ => None,
+    };
+    Some(TrackAnalysis { bpm: (bpm > 0.0).then_some(bpm), key: musical_key })
+}
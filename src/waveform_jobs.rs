@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use crate::loudness::LoudnessMeter;
+use crate::spectrogram::{Spectrogram, SpectrogramAnalyzer};
+use crate::track_analysis::{TrackAnalysis, TrackAnalyzer};
+use crate::waveform::WaveformProfile;
+use crate::waveform_cache::{CacheKey, WaveformCache};
+
+/// Decoder threads shared by all waveform jobs. Jobs beyond this wait in the queue.
+const WORKERS: usize = 2;
+
+/// A submitted decode: partial profiles and then the final one arrive on `rx`,
+/// which disconnects when the job ends (finished, failed or cancelled).
+pub struct WaveformJob {
+    pub generation: u64,
+    pub rx: Receiver<WaveformUpdate>,
+}
+
+pub enum WaveformUpdate {
+    /// Decoded so far; bins past the decode position are empty
+    Partial(WaveformProfile),
+    /// The whole track
+    Done(Decoded),
+}
+
+/// Everything one decode produces besides loudness, which only goes to the cache.
+pub struct Decoded {
+    pub profile: WaveformProfile,
+    pub spectrogram: Option<Spectrogram>,
+    pub analysis: TrackAnalysis,
+}
+
+/// Where a job sends its updates: empty for prefetches, which only fill the
+/// cache until a submit for the same track takes them over.
+type UpdateSlot = Arc<Mutex<Option<Sender<WaveformUpdate>>>>;
+
+struct Job {
+    path: String,
+    key: Option<CacheKey>,
+    cancel: Arc<AtomicBool>,
+    tx: UpdateSlot,
+}
+
+/// The latest prefetch, kept so a submit for its track can take it over.
+#[derive(Default)]
+struct Prefetch {
+    cancel: Arc<AtomicBool>,
+    /// None once the prefetch has ended
+    key: Option<CacheKey>,
+    tx: UpdateSlot,
+}
+
+struct Shared {
+    queue: Mutex<VecDeque<Job>>,
+    ready: Condvar,
+    /// Bumped on every submit or cancel; only the latest generation is wanted.
+    generation: AtomicU64,
+    /// Cancellation token of the latest job, tripped when it is superseded.
+    current_cancel: Mutex<Arc<AtomicBool>>,
+    /// The latest prefetch, whose token is tripped the same way.
+    prefetch: Mutex<Prefetch>,
+    cache: WaveformCache,
+}
+
+/// Decodes waveforms on a small worker pool, for the current track only.
+///
+/// Submitting a job cancels the previous one (mid-decode if it already
+/// started) and drops anything still queued, so skipping through tracks
+/// never piles up decodes. A single prefetch (e.g. for the next queued
+/// track) may run alongside it and is cancelled the same way, unless the
+/// job submitted next is for the same track: then the prefetch becomes that
+/// job rather than decoding the track again from the start. Callers
+/// compare `generation` against `is_current` before using results, which
+/// filters out peaks a stale job sent just before noticing its cancellation.
+#[derive(Clone)]
+pub struct WaveformJobs {
+    shared: Arc<Shared>,
+}
+
+impl WaveformJobs {
+    pub fn new(cache: WaveformCache) -> Self {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(VecDeque::new()),
+            ready: Condvar::new(),
+            generation: AtomicU64::new(0),
+            current_cancel: Mutex::new(Arc::new(AtomicBool::new(false))),
+            prefetch: Mutex::new(Prefetch::default()),
+            cache,
+        });
+        for i in 0..WORKERS {
+            let shared = shared.clone();
+            let spawned = thread::Builder::new()
+                .name(format!("waveform-{}", i))
+                .spawn(move || Self::worker(&shared));
+            if let Err(e) = spawned {
+                log::warn!("Failed to start waveform worker: {}", e);
+            }
+        }
+        Self { shared }
+    }
+
+    /// Queue a decode of `path`, superseding any earlier job. The profile,
+    /// and everything else analysed from the same decode, are stored in the
+    /// cache under `key` once decoding completes.
+    /// A prefetch of the same `key` is taken over, keeping what it has
+    /// decoded so far; any other prefetch is cancelled, as the track changed
+    /// under it.
+    pub fn submit(&self, path: String, key: Option<CacheKey>) -> WaveformJob {
+        let (generation, cancel) = self.supersede();
+        let (tx, rx) = mpsc::channel();
+
+        let mut queue = self.shared.queue.lock().unwrap_or_else(|e| e.into_inner());
+        // Held until the prefetch has its sender, so a worker finishing it
+        // either sees the sender or has already marked it ended
+        let mut prefetch_slot = self.shared.prefetch.lock().unwrap_or_else(|e| e.into_inner());
+        let prefetch = std::mem::take(&mut *prefetch_slot);
+        if key.is_some() && prefetch.key == key {
+            *prefetch.tx.lock().unwrap_or_else(|e| e.into_inner()) = Some(tx);
+            *self.shared.current_cancel.lock().unwrap_or_else(|e| e.into_inner()) = prefetch.cancel.clone();
+            // Keep the prefetch if it hasn't started; everything else is stale
+            queue.retain(|job| Arc::ptr_eq(&job.cancel, &prefetch.cancel));
+        } else {
+            prefetch.cancel.store(true, Ordering::Relaxed);
+            // Anything still queued is stale now; dropping it disconnects its receiver
+            queue.clear();
+            queue.push_back(Job { path, key, cancel, tx: Arc::new(Mutex::new(Some(tx))) });
+            self.shared.ready.notify_one();
+        }
+
+        WaveformJob { generation, rx }
+    }
+
+    /// Decode `path` into the cache in the background, replacing any earlier
+    /// prefetch. Queued behind the current job, which keeps priority.
+    pub fn prefetch(&self, path: String, key: CacheKey) {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let tx = UpdateSlot::default();
+        let mut queue = self.shared.queue.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = std::mem::replace(
+            &mut *self.shared.prefetch.lock().unwrap_or_else(|e| e.into_inner()),
+            Prefetch { cancel: cancel.clone(), key: Some(key.clone()), tx: tx.clone() },
+        );
+        previous.cancel.store(true, Ordering::Relaxed);
+        queue.retain(|job| !job.cancel.load(Ordering::Relaxed));
+        queue.push_back(Job { path, key: Some(key), cancel, tx });
+        self.shared.ready.notify_one();
+    }
+
+    /// Cancel the current job without starting another (e.g. on a cache hit).
+    pub fn cancel(&self) {
+        self.supersede();
+        let mut queue = self.shared.queue.lock().unwrap_or_else(|e| e.into_inner());
+        let prefetch = std::mem::take(&mut *self.shared.prefetch.lock().unwrap_or_else(|e| e.into_inner()));
+        prefetch.cancel.store(true, Ordering::Relaxed);
+        queue.clear();
+    }
+
+    pub fn is_current(&self, generation: u64) -> bool {
+        self.shared.generation.load(Ordering::SeqCst) == generation
+    }
+
+    /// Trip the current token and advance the generation; returns the new
+    /// generation and its fresh token.
+    fn supersede(&self) -> (u64, Arc<AtomicBool>) {
+        let generation = self.shared.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        (generation, Self::renew(&self.shared.current_cancel))
+    }
+
+    /// Trip the token in `slot` and replace it with a fresh one.
+    fn renew(slot: &Mutex<Arc<AtomicBool>>) -> Arc<AtomicBool> {
+        let mut token = slot.lock().unwrap_or_else(|e| e.into_inner());
+        token.store(true, Ordering::Relaxed);
+        *token = Arc::new(AtomicBool::new(false));
+        token.clone()
+    }
+
+    fn worker(shared: &Shared) {
+        loop {
+            let job = {
+                let mut queue = shared.queue.lock().unwrap_or_else(|e| e.into_inner());
+                loop {
+                    if let Some(job) = queue.pop_front() {
+                        break job;
+                    }
+                    queue = shared.ready.wait(queue).unwrap_or_else(|e| e.into_inner());
+                }
+            };
+            if job.cancel.load(Ordering::Relaxed) {
+                continue;
+            }
+            let decoded = Self::decode(shared, &job);
+
+            // An ended prefetch can't be taken over any more; a submit for its
+            // track finds the result in the cache instead
+            {
+                let mut prefetch = shared.prefetch.lock().unwrap_or_else(|e| e.into_inner());
+                if Arc::ptr_eq(&prefetch.cancel, &job.cancel) {
+                    prefetch.key = None;
+                }
+            }
+            let tx = job.tx.lock().unwrap_or_else(|e| e.into_inner()).take();
+            if let (Some(decoded), Some(tx)) = (decoded, tx) {
+                let _ = tx.send(WaveformUpdate::Done(decoded));
+            }
+        }
+    }
+
+    /// Decode one job, sending partial profiles while a receiver is attached,
+    /// and store the results in the cache.
+    fn decode(shared: &Shared, job: &Job) -> Option<Decoded> {
+        let mut on_partial = |partial: WaveformProfile| {
+            if let Some(ref tx) = *job.tx.lock().unwrap_or_else(|e| e.into_inner()) {
+                let _ = tx.send(WaveformUpdate::Partial(partial));
+            }
+        };
+        let mut taps = (LoudnessMeter::default(), (SpectrogramAnalyzer::default(), TrackAnalyzer::default()));
+        let profile = WaveformProfile::from_file(&job.path, &job.cancel, &mut on_partial, Some(&mut taps))?;
+        let (meter, (spectrogram_analyzer, track_analyzer)) = taps;
+        let spectrogram = spectrogram_analyzer.finish();
+        let analysis = track_analyzer.finish();
+        if let Some(ref key) = job.key {
+            if let Err(e) = shared.cache.store(key, &profile) {
+                log::warn!("Failed to cache waveform: {}", e);
+            }
+            if let Some(loudness) = meter.finish() {
+                if let Err(e) = shared.cache.store_loudness(key, &loudness) {
+                    log::warn!("Failed to cache loudness: {}", e);
+                }
+            }
+            if let Err(e) = shared.cache.store_spectrogram(key, spectrogram.as_ref()) {
+                log::warn!("Failed to cache spectrogram: {}", e);
+            }
+            if let Err(e) = shared.cache.store_analysis(key, &analysis) {
+                log::warn!("Failed to cache tempo and key: {}", e);
+            }
+            shared.cache.evict();
+        }
+        Some(Decoded { profile, spectrogram, analysis })
+    }
+}